@@ -18,9 +18,35 @@
 
 //! Build AST trees from Gemtext
 
+pub mod borrowed;
 mod container;
+pub mod cst;
+mod diagnostic;
+mod diff;
+mod fold;
 mod node;
+mod parser;
+mod reparse;
+mod section;
+mod slug;
+mod span;
+mod visit;
+
+#[cfg(feature = "url")] mod resolve;
 
 #[cfg(feature = "macros")] mod macros;
 
-pub use {container::Ast, node::Node};
+#[cfg(feature = "url")]
+pub use resolve::{InvalidLink, LinkTarget, RelativeReference};
+pub use {
+  container::Ast,
+  diagnostic::{Diagnostic, DiagnosticKind, ParseErrors, Severity},
+  diff::{diff, Conflict, Diff, Operation},
+  fold::Fold,
+  node::Node,
+  parser::Parser,
+  section::{Outline, Section},
+  slug::{slug, Slugger},
+  span::{NodeSpan, Position, Span},
+  visit::Visit,
+};
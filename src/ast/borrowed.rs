@@ -0,0 +1,263 @@
+// This file is part of Germ <https://github.com/gemrest/germ>.
+// Copyright (C) 2022-2022 Fuwn <contact@fuwn.me>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+//
+// Copyright (C) 2022-2022 Fuwn <contact@fuwn.me>
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Build AST trees which borrow from their Gemtext source
+//!
+//! The [`Ast`] and [`Node`] of this module mirror [`super::Ast`] and
+//! [`super::Node`], but slice straight into the source instead of allocating
+//! a fresh [`String`] for every piece of text. Text is only copied where the
+//! source has to be normalised, e.g., CRLF line endings in a preformatted
+//! block.
+
+use {super::NodeSpan, std::borrow::Cow};
+
+/// An AST structure which borrows from its Gemtext source
+///
+/// # Example
+///
+/// ```rust
+/// let source = "=> gemini://gem.rest/ GemRest".to_string();
+/// let ast = germ::ast::borrowed::Ast::from_value(&source);
+///
+/// assert_eq!(ast.into_owned(), germ::ast::Ast::from_string(source.clone()));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Ast<'a> {
+  inner: Vec<Node<'a>>,
+  // Borrowed when converting from another AST tree, since most conversions
+  // never look at the spans.
+  spans: Cow<'a, [NodeSpan]>,
+}
+
+impl PartialEq for Ast<'_> {
+  fn eq(&self, other: &Self) -> bool { self.inner == other.inner }
+}
+
+impl Eq for Ast<'_> {}
+
+impl<'a> Ast<'a> {
+  /// Build an AST tree which borrows from Gemtext
+  ///
+  /// # Example
+  ///
+  /// ```rust
+  /// let _ =
+  ///   germ::ast::borrowed::Ast::from_value(r#"=> gemini://gem.rest/ GemRest"#);
+  /// ```
+  #[must_use]
+  pub fn from_value(value: &'a str) -> Self {
+    let (inner, spans, _) = super::parser::parse(value);
+
+    Self { inner, spans: Cow::Owned(spans) }
+  }
+
+  /// Build an AST tree from a [`Vec`] of [`Node`]s
+  #[must_use]
+  pub const fn from_nodes(nodes: Vec<Node<'a>>) -> Self {
+    Self { inner: nodes, spans: Cow::Borrowed(&[]) }
+  }
+
+  /// Convert the `Ast` into an [`Ast`](super::Ast) which owns all of its text
+  ///
+  /// # Example
+  ///
+  /// ```rust
+  /// let _: germ::ast::Ast =
+  ///   germ::ast::borrowed::Ast::from_value("# Hi").into_owned();
+  /// ```
+  #[must_use]
+  pub fn into_owned(self) -> super::Ast { super::Ast::from(self) }
+
+  #[must_use]
+  pub fn to_gemtext(&self) -> String {
+    let mut gemtext = String::new();
+
+    for node in &self.inner {
+      match node {
+        Node::Text(text) => gemtext.push_str(&format!("{text}\n")),
+        Node::Link { to, text } => gemtext.push_str(&format!(
+          "=> {}{}\n",
+          to,
+          text.as_ref().map_or_else(String::new, |text| format!(" {text}")),
+        )),
+        Node::Heading { level, text } =>
+          gemtext.push_str(&format!("{} {}\n", "#".repeat(*level), text)),
+        Node::List(items) => gemtext.push_str(&format!(
+          "{}\n",
+          items
+            .iter()
+            .map(|i| format!("* {i}"))
+            .collect::<Vec<String>>()
+            .join("\n"),
+        )),
+        Node::Blockquote(text) => gemtext.push_str(&format!("> {text}\n")),
+        Node::PreformattedText { alt_text, text } =>
+          gemtext.push_str(&format!(
+            "```{}\n{}```\n",
+            alt_text.as_deref().unwrap_or_default(),
+            text
+          )),
+        Node::Whitespace => gemtext.push('\n'),
+      }
+    }
+
+    if gemtext.ends_with('\n') && !gemtext.ends_with("\n\n") {
+      gemtext.pop();
+    }
+
+    gemtext
+  }
+
+  /// Walk the nodes of `Ast` with a [`Visit`](super::Visit)or, in order
+  pub fn visit(&self, visitor: &mut impl super::Visit) {
+    for node in &self.inner {
+      visitor.visit_node(node);
+    }
+  }
+
+  /// The actual AST of `Ast`
+  #[must_use]
+  pub const fn inner(&self) -> &Vec<Node<'a>> { &self.inner }
+
+  /// The source locations of each [`Node`] of `Ast`, by index
+  ///
+  /// See [`Ast::spans`](super::Ast::spans).
+  #[must_use]
+  pub fn spans(&self) -> &[NodeSpan] { &self.spans }
+}
+
+impl From<Ast<'_>> for super::Ast {
+  fn from(ast: Ast<'_>) -> Self {
+    Self::from_parts(
+      ast.inner.into_iter().map(super::Node::from).collect(),
+      ast.spans.into_owned(),
+    )
+  }
+}
+
+impl<'a> From<&'a super::Ast> for Ast<'a> {
+  fn from(ast: &'a super::Ast) -> Self {
+    Self {
+      inner: ast.inner().iter().map(Node::from).collect(),
+      spans: Cow::Borrowed(ast.spans()),
+    }
+  }
+}
+
+impl<'a> From<&'a Ast<'_>> for Ast<'a> {
+  fn from(ast: &'a Ast<'_>) -> Self {
+    Self {
+      inner: ast.inner.iter().map(Node::from).collect(),
+      spans: Cow::Borrowed(&ast.spans),
+    }
+  }
+}
+
+/// A Gemtext AST node which borrows from its Gemtext source
+///
+/// See [`super::Node`] for a description of each node type.
+#[derive(Debug, PartialEq, Clone, Eq)]
+pub enum Node<'a> {
+  /// A text line
+  Text(Cow<'a, str>),
+  /// A link line
+  Link {
+    /// The location that a link line is pointing to
+    to:   Cow<'a, str>,
+    /// The text a link line *may* have
+    text: Option<Cow<'a, str>>,
+  },
+  /// A heading line
+  Heading {
+    /// The level of a heading
+    level: usize,
+    /// The text of a heading
+    text:  Cow<'a, str>,
+  },
+  /// A collection of sequential list item lines
+  List(Vec<Cow<'a, str>>),
+  /// A blockquote line
+  Blockquote(Cow<'a, str>),
+  /// A preformatted block
+  PreformattedText {
+    /// A preformatted blocks alt-text
+    alt_text: Option<Cow<'a, str>>,
+    /// A preformatted blocks content
+    text:     Cow<'a, str>,
+  },
+  /// A whitespace line, a line which contains nothing but whitespace.
+  Whitespace,
+}
+
+impl Node<'_> {
+  /// Convert the `Node` into a [`Node`](super::Node) which owns all of its
+  /// text
+  #[must_use]
+  pub fn into_owned(self) -> super::Node { super::Node::from(self) }
+
+  /// Convert a single [`Node`] of any node type to a Gemtext [`String`]
+  #[must_use]
+  pub fn to_gemtext(&self) -> String {
+    Ast::from_nodes(vec![Node::from(self)]).to_gemtext()
+  }
+}
+
+impl<'a> From<&'a super::Node> for Node<'a> {
+  fn from(node: &'a super::Node) -> Self {
+    match node {
+      super::Node::Text(text) => Self::Text(Cow::Borrowed(text)),
+      super::Node::Link { to, text } => Self::Link {
+        to:   Cow::Borrowed(to),
+        text: text.as_deref().map(Cow::Borrowed),
+      },
+      super::Node::Heading { level, text } =>
+        Self::Heading { level: *level, text: Cow::Borrowed(text) },
+      super::Node::List(items) =>
+        Self::List(items.iter().map(|item| Cow::Borrowed(&**item)).collect()),
+      super::Node::Blockquote(text) => Self::Blockquote(Cow::Borrowed(text)),
+      super::Node::PreformattedText { alt_text, text } =>
+        Self::PreformattedText {
+          alt_text: alt_text.as_deref().map(Cow::Borrowed),
+          text:     Cow::Borrowed(text),
+        },
+      super::Node::Whitespace => Self::Whitespace,
+    }
+  }
+}
+
+impl<'a> From<&'a Node<'_>> for Node<'a> {
+  fn from(node: &'a Node<'_>) -> Self {
+    match node {
+      Node::Text(text) => Self::Text(Cow::Borrowed(text)),
+      Node::Link { to, text } => Self::Link {
+        to:   Cow::Borrowed(to),
+        text: text.as_deref().map(Cow::Borrowed),
+      },
+      Node::Heading { level, text } =>
+        Self::Heading { level: *level, text: Cow::Borrowed(text) },
+      Node::List(items) =>
+        Self::List(items.iter().map(|item| Cow::Borrowed(&**item)).collect()),
+      Node::Blockquote(text) => Self::Blockquote(Cow::Borrowed(text)),
+      Node::PreformattedText { alt_text, text } => Self::PreformattedText {
+        alt_text: alt_text.as_deref().map(Cow::Borrowed),
+        text:     Cow::Borrowed(text),
+      },
+      Node::Whitespace => Self::Whitespace,
+    }
+  }
+}
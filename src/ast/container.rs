@@ -16,7 +16,7 @@
 // Copyright (C) 2022-2022 Fuwn <contact@fuwn.me>
 // SPDX-License-Identifier: GPL-3.0-only
 
-use super::Node;
+use super::{Diagnostic, Fold, Node, NodeSpan, ParseErrors, Severity, Visit};
 
 /// An AST structure which contains an AST tree
 ///
@@ -25,11 +25,24 @@ use super::Node;
 /// ```rust
 /// let _ = germ::ast::Ast::from_string(r#"=> gemini://gem.rest/ GemRest"#);
 /// ```
-#[derive(Debug, Clone, PartialEq, Eq)]
+///
+/// Two `Ast`s are equal when their nodes are equal, regardless of where in
+/// their sources those nodes came from.
+///
+/// With the `serde` feature, an `Ast` is (de)serialised as an array of its
+/// [`Node`]s. Spans are not serialised, so a deserialised `Ast` has none.
+#[derive(Debug, Clone)]
 pub struct Ast {
   inner: Vec<Node>,
+  spans: Vec<NodeSpan>,
 }
 
+impl PartialEq for Ast {
+  fn eq(&self, other: &Self) -> bool { self.inner == other.inner }
+}
+
+impl Eq for Ast {}
+
 impl Ast {
   /// Build an AST tree from Gemtext
   ///
@@ -58,6 +71,8 @@ impl Ast {
 
   /// Build an AST tree from a value
   ///
+  /// Invalid Gemtext is recovered from like [`Ast::from_str_lenient`] does.
+  ///
   /// # Example
   ///
   /// ```rust
@@ -65,32 +80,60 @@ impl Ast {
   /// ```
   #[must_use]
   pub fn from_value(value: &(impl ToString + ?Sized)) -> Self {
-    let mut ast = vec![];
-    let mut in_preformatted = false;
-    let mut in_list = false;
-    let source = value.to_string();
-    let mut lines = source.lines();
-
-    // Iterate over all lines in the Gemtext `source`
-    while let Some(line) = lines.next() {
-      // Evaluate the Gemtext line and append its AST node to the `ast` tree
-      ast.append(&mut Self::evaluate(
-        line,
-        &mut lines,
-        &mut in_preformatted,
-        &mut in_list,
-      ));
-    }
+    super::borrowed::Ast::from_value(&value.to_string()).into_owned()
+  }
 
-    if source.chars().last().map_or(false, |c| c == '\n') {
-      if let Some(last) = ast.last() {
-        if !matches!(last, Node::Whitespace) {
-          ast.push(Node::Whitespace);
-        }
-      }
+  /// Build an AST tree from Gemtext, failing if the Gemtext is invalid
+  ///
+  /// # Example
+  ///
+  /// ```rust
+  /// assert!(germ::ast::Ast::try_from_str("=> / Home").is_ok());
+  /// assert!(germ::ast::Ast::try_from_str("=>").is_err());
+  /// ```
+  ///
+  /// # Errors
+  ///
+  /// - Errors with every [`Diagnostic`] of the Gemtext if any of them is a
+  ///   [`Severity::Error`]
+  pub fn try_from_str(value: &str) -> Result<Self, ParseErrors> {
+    let (ast, diagnostics) = Self::from_str_lenient(value);
+
+    if diagnostics
+      .iter()
+      .any(|diagnostic| diagnostic.severity() == Severity::Error)
+    {
+      Err(ParseErrors::new(diagnostics))
+    } else {
+      Ok(ast)
     }
+  }
+
+  /// Build an AST tree from Gemtext, recovering from invalid Gemtext and
+  /// collecting a [`Diagnostic`] for each problem found
+  ///
+  /// Link lines without a location become text lines, and preformatted
+  /// blocks without a closing toggle are closed at the end of the Gemtext.
+  ///
+  /// # Example
+  ///
+  /// ```rust
+  /// use germ::ast::{Ast, DiagnosticKind, Node};
+  ///
+  /// let (ast, diagnostics) = Ast::from_str_lenient("=>\n#### Deep");
+  ///
+  /// assert_eq!(ast.inner()[0], Node::Text("=>".to_string()));
+  /// assert_eq!(diagnostics[0].kind(), DiagnosticKind::EmptyLink);
+  /// assert_eq!(diagnostics[1].kind(), DiagnosticKind::HeadingTooDeep);
+  /// ```
+  #[must_use]
+  pub fn from_str_lenient(value: &str) -> (Self, Vec<Diagnostic>) {
+    let (inner, spans, diagnostics) = super::parser::parse(value);
 
-    Self { inner: ast }
+    (
+      Self::from_parts(inner.into_iter().map(Node::from).collect(), spans),
+      diagnostics,
+    )
   }
 
   /// Build an AST tree from a [`Vec`] of [`Node`]s
@@ -110,46 +153,50 @@ impl Ast {
   /// );
   /// ```
   #[must_use]
-  pub fn from_nodes(nodes: Vec<Node>) -> Self { Self { inner: nodes } }
+  pub const fn from_nodes(nodes: Vec<Node>) -> Self {
+    Self { inner: nodes, spans: Vec::new() }
+  }
+
+  pub(crate) const fn from_parts(
+    inner: Vec<Node>,
+    spans: Vec<NodeSpan>,
+  ) -> Self {
+    Self { inner, spans }
+  }
 
   #[must_use]
   pub fn to_gemtext(&self) -> String {
-    let mut gemtext = String::new();
+    super::borrowed::Ast::from(self).to_gemtext()
+  }
 
+  /// Walk the nodes of `Ast` with a [`Visit`]or, in order
+  ///
+  /// See [`Visit`] for an example.
+  pub fn visit(&self, visitor: &mut impl Visit) {
     for node in &self.inner {
-      match node {
-        Node::Text(text) => gemtext.push_str(&format!("{text}\n")),
-        Node::Link { to, text } => gemtext.push_str(&format!(
-          "=> {}{}\n",
-          to,
-          text.clone().map_or_else(String::new, |text| format!(" {text}")),
-        )),
-        Node::Heading { level, text } =>
-          gemtext.push_str(&format!("{} {}\n", "#".repeat(*level), text)),
-        Node::List(items) => gemtext.push_str(&format!(
-          "{}\n",
-          items
-            .iter()
-            .map(|i| format!("* {i}"))
-            .collect::<Vec<String>>()
-            .join("\n"),
-        )),
-        Node::Blockquote(text) => gemtext.push_str(&format!("> {text}\n")),
-        Node::PreformattedText { alt_text, text } =>
-          gemtext.push_str(&format!(
-            "```{}\n{}```\n",
-            alt_text.clone().unwrap_or_default(),
-            text
-          )),
-        Node::Whitespace => gemtext.push('\n'),
-      }
-    }
-
-    if gemtext.ends_with('\n') && !gemtext.ends_with("\n\n") {
-      gemtext.pop();
+      visitor.visit_node(&super::borrowed::Node::from(node));
     }
+  }
 
-    gemtext
+  /// Rewrite the nodes of `Ast` with a [`Fold`]er, in order
+  ///
+  /// The nodes which are kept keep their spans.
+  ///
+  /// See [`Fold`] for an example.
+  #[must_use]
+  pub fn fold(self, folder: &mut impl Fold) -> Self {
+    let mut spans = self.spans.into_iter();
+    let (inner, spans) = self
+      .inner
+      .into_iter()
+      .filter_map(|node| {
+        let span = spans.next();
+
+        folder.fold_node(node).map(|node| (node, span))
+      })
+      .unzip::<_, _, Vec<_>, Vec<_>>();
+
+    Self { inner, spans: spans.into_iter().flatten().collect() }
   }
 
   /// The actual AST of `Ast`
@@ -163,157 +210,244 @@ impl Ast {
   #[must_use]
   pub const fn inner(&self) -> &Vec<Node> { &self.inner }
 
-  #[allow(clippy::too_many_lines)]
-  fn evaluate(
-    line: &str,
-    lines: &mut std::str::Lines<'_>,
-    in_preformatted: &mut bool,
-    in_list: &mut bool,
-  ) -> Vec<Node> {
-    let mut preformatted = String::new();
-    let mut alt_text = String::new();
-    let mut nodes = vec![];
-    let mut line = line;
-    let mut list_items = vec![];
-
-    // Enter a not-so-infinite loop as sometimes, we may need to stay in an
-    // evaluation loop, e.g., multiline contexts: preformatted text, lists, etc.
-    loop {
-      // Match the first character of the Gemtext line to understand the line
-      // type
-      match line.get(0..1).unwrap_or("") {
-        "=" if !*in_preformatted => {
-          // If the Gemtext line starts with an "=" ("=>"), it is a link line,
-          // so splitting it up should be easy enough.
-          let line = line.get(2..).unwrap();
-          let mut split = line
-            .split_whitespace()
-            .map(String::from)
-            .collect::<Vec<String>>()
-            .into_iter();
-
-          nodes.push(Node::Link {
-            to:   split.next().expect("no location in link"),
-            text: {
-              let rest = split.collect::<Vec<String>>().join(" ");
-
-              if rest.is_empty() { None } else { Some(rest) }
-            },
-          });
-
-          break;
-        }
-        "#" if !*in_preformatted => {
-          // If the Gemtext line starts with an "#", it is a heading, so let's
-          // find out how deep it goes.
-          let level =
-            line.trim_start().chars().take_while(|&c| c == '#').count();
-
-          nodes.push(Node::Heading {
-            level,
-            // Here, we are `get`ing the `&str` starting at the `level`-th
-            // index, then trimming the start. These operations
-            // effectively off the line identifier.
-            text: line.get(level..).unwrap_or("").trim_start().to_string(),
-          });
-
-          break;
-        }
-        "*" if !*in_preformatted => {
-          // If the Gemtext line starts with an asterisk, it is a list item, so
-          // let's enter a list context.
-          if !*in_list {
-            *in_list = true;
-          }
-
-          list_items.push(line.get(1..).unwrap_or("").trim_start().to_string());
-
-          if let Some(next_line) = lines.next() {
-            line = next_line;
-          } else {
-            break;
-          }
-        }
-        ">" if !*in_preformatted => {
-          // If the Gemtext line starts with an ">", it is a blockquote, so
-          // let's just clip off the line identifier.
-          nodes.push(Node::Blockquote(
-            line.get(1..).unwrap_or("").trim_start().to_string(),
-          ));
-
-          break;
-        }
-        "`" => {
-          // If the Gemtext line starts with a backtick, it's a preformatted
-          // toggle, so let's enter a preformatted text context.
-          *in_preformatted = !*in_preformatted;
-
-          if *in_preformatted {
-            alt_text = line.get(3..).unwrap_or("").to_string();
-
-            if let Some(next_line) = lines.next() {
-              line = next_line;
-            } else {
-              break;
-            }
-          } else {
-            nodes.push(Node::PreformattedText {
-              alt_text: if alt_text.is_empty() { None } else { Some(alt_text) },
-              text:     preformatted,
-            });
-
-            break;
-          }
-        }
-        "" if !*in_preformatted => {
-          if line.is_empty() {
-            // If the line has nothing on it, it is a whitespace line, as long
-            // as we aren't in a preformatted line context.
-            nodes.push(Node::Whitespace);
-          } else {
-            nodes.push(Node::Text(line.to_string()));
-          }
-
-          break;
-        }
-        // This as a catchall. It does a number of things.
-        _ => {
-          if *in_preformatted {
-            // If we are in a preformatted line context, add the line to the
-            // preformatted blocks content and increment the line.
-            preformatted.push_str(&format!("{line}\n"));
-
-            if let Some(next_line) = lines.next() {
-              line = next_line;
-            } else {
-              break;
-            }
-          } else {
-            // If we are in a list item and hit a catchall, that must mean that
-            // we encountered a line which is not a list line, so
-            // let's stop adding items to the list context.
-            if *in_list {
-              *in_list = false;
-
-              nodes.push(Node::Text(line.to_string()));
-
-              break;
-            }
-
-            nodes.push(Node::Text(line.to_string()));
-
-            break;
-          }
-        }
-      }
+  /// The source locations of each [`Node`] of `Ast`, by index
+  ///
+  /// The spans are only known for `Ast`s which were parsed from Gemtext; an
+  /// `Ast` built from a [`Vec`] of [`Node`]s has none, and an `Ast` forgets
+  /// them once nodes without a source are added to it, or once its nodes are
+  /// borrowed mutably.
+  ///
+  /// # Example
+  ///
+  /// ```rust
+  /// let ast = germ::ast::Ast::from_string("# Hi\n=> / Home");
+  /// let link = &ast.spans()[1];
+  ///
+  /// assert_eq!(link.span().start().line(), 2);
+  /// assert_eq!(link.text().unwrap().start().column(), 6);
+  /// ```
+  #[must_use]
+  pub fn spans(&self) -> &[NodeSpan] { &self.spans }
+
+  /// The number of nodes in `Ast`
+  #[must_use]
+  pub fn len(&self) -> usize { self.inner.len() }
+
+  /// Whether `Ast` has no nodes
+  #[must_use]
+  pub fn is_empty(&self) -> bool { self.inner.is_empty() }
+
+  /// An iterator over the nodes of `Ast`
+  pub fn iter(&self) -> std::slice::Iter<'_, Node> { self.inner.iter() }
+
+  /// An iterator which allows modifying each node of `Ast`
+  ///
+  /// The modified nodes no longer match their source, so `Ast` forgets its
+  /// spans.
+  ///
+  /// # Example
+  ///
+  /// ```rust
+  /// let mut ast = germ::ast::Ast::from_string("## Hi\n## There");
+  ///
+  /// for node in ast.iter_mut() {
+  ///   if let germ::ast::Node::Heading { level, .. } = node {
+  ///     *level -= 1;
+  ///   }
+  /// }
+  ///
+  /// assert_eq!(ast.to_gemtext(), "# Hi\n# There");
+  /// ```
+  pub fn iter_mut(&mut self) -> std::slice::IterMut<'_, Node> {
+    self.spans.clear();
+    self.inner.iter_mut()
+  }
+
+  /// Append a node to the end of `Ast`
+  ///
+  /// # Example
+  ///
+  /// ```rust
+  /// let mut ast = germ::ast::Ast::from_string("# Hi");
+  ///
+  /// ast.push(germ::ast::Node::Text("There".to_string()));
+  ///
+  /// assert_eq!(ast.to_gemtext(), "# Hi\nThere");
+  /// ```
+  pub fn push(&mut self, node: Node) {
+    self.spans.clear();
+    self.inner.push(node);
+  }
+
+  /// Insert a node at `index`, shifting every node after it
+  ///
+  /// # Panics
+  ///
+  /// - Panics if `index` is greater than the number of nodes
+  pub fn insert(&mut self, index: usize, node: Node) {
+    self.spans.clear();
+    self.inner.insert(index, node);
+  }
+
+  /// Remove and return the node at `index`, shifting every node after it
+  ///
+  /// # Panics
+  ///
+  /// - Panics if `index` is out of bounds
+  pub fn remove(&mut self, index: usize) -> Node {
+    if index < self.spans.len() {
+      self.spans.remove(index);
     }
 
-    if !list_items.is_empty() {
-      nodes.reverse();
-      nodes.push(Node::List(list_items));
-      nodes.reverse();
+    self.inner.remove(index)
+  }
+
+  /// Keep only the nodes for which `keep` returns `true`, in order
+  ///
+  /// # Example
+  ///
+  /// ```rust
+  /// let mut ast = germ::ast::Ast::from_string("# Hi\n\nThere");
+  ///
+  /// ast.retain(|node| *node != germ::ast::Node::Whitespace);
+  ///
+  /// assert_eq!(ast.to_gemtext(), "# Hi\nThere");
+  /// ```
+  pub fn retain(&mut self, keep: impl FnMut(&Node) -> bool) {
+    let kept = self.inner.iter().map(keep).collect::<Vec<bool>>();
+    let mut nodes = kept.iter();
+    let mut spans = kept.iter();
+
+    self.inner.retain(|_| nodes.next() == Some(&true));
+    self.spans.retain(|_| spans.next() == Some(&true));
+  }
+
+  /// Replace the body of the section under the first heading whose text is
+  /// `heading`, returning the nodes which were replaced
+  ///
+  /// A section runs up to the next heading of the same or a higher level.
+  /// `None` is returned, and nothing is replaced, if there is no such heading.
+  ///
+  /// # Example
+  ///
+  /// ```rust
+  /// use germ::ast::{Ast, Node};
+  ///
+  /// let mut ast = Ast::from_string("# A\n## B\nOld\n### C\n## D");
+  ///
+  /// ast.replace_section("B", vec![Node::Text("New".to_string())]);
+  ///
+  /// assert_eq!(ast.to_gemtext(), "# A\n## B\nNew\n## D");
+  /// ```
+  pub fn replace_section(
+    &mut self,
+    heading: &str,
+    nodes: impl IntoIterator<Item = Node>,
+  ) -> Option<Vec<Node>> {
+    let section = self.section(heading)?;
+
+    self.spans.clear();
+
+    Some(self.inner.splice(section.start + 1..section.end, nodes).collect())
+  }
+
+  /// Remove the section under the first heading whose text is `heading`,
+  /// heading included, returning the nodes which were removed
+  ///
+  /// See [`Ast::replace_section`] for where a section ends.
+  pub fn remove_section(&mut self, heading: &str) -> Option<Vec<Node>> {
+    let section = self.section(heading)?;
+
+    if section.end <= self.spans.len() {
+      self.spans.drain(section.clone());
     }
 
-    nodes
+    Some(self.inner.drain(section).collect())
+  }
+
+  /// The range of nodes which make up the section under the first heading
+  /// whose text is `heading`
+  fn section(&self, heading: &str) -> Option<std::ops::Range<usize>> {
+    let (start, level) =
+      self.inner.iter().enumerate().find_map(|(index, node)| match node {
+        Node::Heading { level, text } if text == heading =>
+          Some((index, *level)),
+        _ => None,
+      })?;
+    let end = self.inner[start + 1..]
+      .iter()
+      .position(|node| {
+        matches!(node, Node::Heading { level: next, .. } if *next <= level)
+      })
+      .map_or(self.inner.len(), |offset| start + 1 + offset);
+
+    Some(start..end)
+  }
+}
+
+impl<I: std::slice::SliceIndex<[Node]>> std::ops::Index<I> for Ast {
+  type Output = I::Output;
+
+  fn index(&self, index: I) -> &Self::Output { &self.inner[index] }
+}
+
+impl<I: std::slice::SliceIndex<[Node]>> std::ops::IndexMut<I> for Ast {
+  fn index_mut(&mut self, index: I) -> &mut Self::Output {
+    self.spans.clear();
+
+    &mut self.inner[index]
+  }
+}
+
+impl IntoIterator for Ast {
+  type IntoIter = std::vec::IntoIter<Node>;
+  type Item = Node;
+
+  fn into_iter(self) -> Self::IntoIter { self.inner.into_iter() }
+}
+
+impl<'a> IntoIterator for &'a Ast {
+  type IntoIter = std::slice::Iter<'a, Node>;
+  type Item = &'a Node;
+
+  fn into_iter(self) -> Self::IntoIter { self.inner.iter() }
+}
+
+impl<'a> IntoIterator for &'a mut Ast {
+  type IntoIter = std::slice::IterMut<'a, Node>;
+  type Item = &'a mut Node;
+
+  fn into_iter(self) -> Self::IntoIter { self.iter_mut() }
+}
+
+impl Extend<Node> for Ast {
+  fn extend<T: IntoIterator<Item = Node>>(&mut self, nodes: T) {
+    self.spans.clear();
+    self.inner.extend(nodes);
+  }
+}
+
+impl FromIterator<Node> for Ast {
+  fn from_iter<T: IntoIterator<Item = Node>>(nodes: T) -> Self {
+    Self::from_nodes(nodes.into_iter().collect())
+  }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Ast {
+  fn serialize<S: serde::Serializer>(
+    &self,
+    serializer: S,
+  ) -> Result<S::Ok, S::Error> {
+    self.inner.serialize(serializer)
+  }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Ast {
+  fn deserialize<D: serde::Deserializer<'de>>(
+    deserializer: D,
+  ) -> Result<Self, D::Error> {
+    Vec::deserialize(deserializer).map(Self::from_nodes)
   }
 }
@@ -0,0 +1,453 @@
+// This file is part of Germ <https://github.com/gemrest/germ>.
+// Copyright (C) 2022-2022 Fuwn <contact@fuwn.me>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+//
+// Copyright (C) 2022-2022 Fuwn <contact@fuwn.me>
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Build lossless concrete syntax trees from Gemtext
+//!
+//! An [`Ast`](super::Ast) only keeps what a Gemtext document means, so
+//! converting it back to Gemtext normalises the document. A [`Cst`] keeps
+//! every byte of its source as well, down to the whitespace between a line's
+//! parts and the line endings, so that a document can be edited in place and
+//! written back out without touching the lines which were not edited.
+//!
+//! # Example
+//!
+//! ```rust
+//! use germ::ast::cst::{Cst, Node};
+//!
+//! let source = "=>\t/x  Foo\r\n*foo\n";
+//! let mut cst = Cst::from_value(source);
+//!
+//! assert_eq!(cst.to_gemtext(), source);
+//!
+//! if let Node::Link(line) = &mut cst.nodes_mut()[0] {
+//!   *line.to_mut() = "/y".into();
+//! }
+//!
+//! assert_eq!(cst.to_gemtext(), "=>\t/y  Foo\r\n*foo\n");
+//! ```
+
+use {
+  super::borrowed,
+  std::{borrow::Cow, fmt},
+};
+
+/// A lossless concrete syntax tree of Gemtext
+///
+/// The nodes of a `Cst` are the same as those of an [`Ast`](super::Ast), but
+/// each one holds the [`Line`]s it was parsed from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cst<'a> {
+  nodes: Vec<Node<'a>>,
+}
+
+impl<'a> Cst<'a> {
+  /// Build a concrete syntax tree from Gemtext
+  ///
+  /// Invalid Gemtext is recovered from like
+  /// [`Ast::from_str_lenient`](super::Ast::from_str_lenient) does.
+  ///
+  /// # Example
+  ///
+  /// ```rust
+  /// let _ = germ::ast::cst::Cst::from_value("=> gemini://gem.rest/ GemRest");
+  /// ```
+  #[must_use]
+  pub fn from_value(value: &'a str) -> Self {
+    let (nodes, spans, _) = super::parser::parse(value);
+    let mut lines = RawLines { source: value, offset: 0 }.peekable();
+    let mut cst = vec![];
+
+    for (node, span) in nodes.iter().zip(&spans) {
+      let end = span.span().end().offset();
+      let mut taken = vec![];
+
+      // Every line which starts within a node belongs to it. The whitespace
+      // node which the parser appends for a final line ending owns no line.
+      while let Some(line) = lines.next_if(|(start, _, _)| *start <= end) {
+        taken.push(line);
+      }
+
+      if let Some(node) = Node::new(node, taken) {
+        cst.push(node);
+      }
+    }
+
+    Self { nodes: cst }
+  }
+
+  /// The nodes of the `Cst`
+  #[must_use]
+  pub fn nodes(&self) -> &[Node<'a>] { &self.nodes }
+
+  /// Obtain mutable access to the nodes of the `Cst`
+  pub fn nodes_mut(&mut self) -> &mut Vec<Node<'a>> { &mut self.nodes }
+
+  /// Lower the `Cst` into an [`Ast`](borrowed::Ast), dropping everything but
+  /// what the Gemtext means
+  ///
+  /// # Example
+  ///
+  /// ```rust
+  /// let source = "=>\t/x  Foo\n*foo";
+  ///
+  /// assert_eq!(
+  ///   germ::ast::cst::Cst::from_value(source).lower().into_owned(),
+  ///   germ::ast::Ast::from_string(source)
+  /// );
+  /// ```
+  #[must_use]
+  pub fn lower(&self) -> borrowed::Ast<'_> {
+    let mut nodes = self.nodes.iter().map(Node::lower).collect::<Vec<_>>();
+
+    // A source which ends with a line ending ends with a whitespace line, just
+    // like the parser sees it.
+    if let Some(last) = self.nodes.last() {
+      if !matches!(last, Node::Whitespace(_))
+        && last.lines().last().map_or(false, |line| line.ending.is_some())
+      {
+        nodes.push(borrowed::Node::Whitespace);
+      }
+    }
+
+    borrowed::Ast::from_nodes(nodes)
+  }
+
+  /// Convert the `Cst` back to Gemtext, byte for byte as it was parsed,
+  /// except for any edits
+  #[must_use]
+  pub fn to_gemtext(&self) -> String {
+    self.nodes.iter().map(ToString::to_string).collect()
+  }
+}
+
+/// A Gemtext CST node
+///
+/// See [`super::Node`] for a description of each node type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Node<'a> {
+  /// A text line
+  Text(Line<'a>),
+  /// A link line
+  Link(Line<'a>),
+  /// A heading line
+  Heading(Line<'a>),
+  /// A collection of sequential list item lines
+  List(Vec<Line<'a>>),
+  /// A blockquote line
+  Blockquote(Line<'a>),
+  /// A preformatted block
+  PreformattedText {
+    /// The opening toggle line, whose text is the alt-text
+    open:  Line<'a>,
+    /// The lines of the content
+    body:  Vec<Line<'a>>,
+    /// The closing toggle line, if the block was closed
+    close: Option<Line<'a>>,
+  },
+  /// A whitespace line
+  Whitespace(Line<'a>),
+}
+
+impl<'a> Node<'a> {
+  /// Split the `lines` of a parsed `node` into their parts
+  fn new(
+    node: &borrowed::Node<'_>,
+    lines: Vec<(usize, &'a str, Option<LineEnding>)>,
+  ) -> Option<Self> {
+    let mut lines = lines.into_iter();
+
+    Some(match node {
+      borrowed::Node::Text(_) => Self::Text(Line::plain(lines.next()?)),
+      borrowed::Node::Whitespace => {
+        let (_, _, ending) = lines.next()?;
+
+        Self::Whitespace(Line { ending, ..Line::default() })
+      }
+      borrowed::Node::Link { .. } => {
+        let (_, text, ending) = lines.next()?;
+        let (marker, rest) = text.split_at(2);
+        let (spacing, rest) = split_whitespace(rest);
+        let (to, rest) =
+          rest.split_at(rest.find(char::is_whitespace).unwrap_or(rest.len()));
+        let (separator, text) = split_whitespace(rest);
+
+        Self::Link(Line {
+          marker: Cow::Borrowed(marker),
+          spacing: Cow::Borrowed(spacing),
+          to: Cow::Borrowed(to),
+          separator: Cow::Borrowed(separator),
+          text: Cow::Borrowed(text),
+          ending,
+        })
+      }
+      borrowed::Node::Heading { level, .. } =>
+        Self::Heading(Line::marked(lines.next()?, *level)),
+      borrowed::Node::List(_) =>
+        Self::List(lines.map(|line| Line::marked(line, 1)).collect()),
+      borrowed::Node::Blockquote(_) =>
+        Self::Blockquote(Line::marked(lines.next()?, 1)),
+      borrowed::Node::PreformattedText { .. } => {
+        let open = Line::toggle(lines.next()?);
+        let mut body = lines.collect::<Vec<_>>();
+        // Only a closed block ends with a toggle line, as any other line
+        // starting with a backtick would have closed it.
+        let close = body
+          .last()
+          .filter(|(_, text, _)| text.starts_with('`'))
+          .copied()
+          .map(Line::toggle);
+
+        if close.is_some() {
+          body.pop();
+        }
+
+        let body = body.into_iter().map(Line::plain).collect();
+
+        Self::PreformattedText { open, body, close }
+      }
+    })
+  }
+
+  /// The [`Line`]s of the `Node`, in source order
+  #[must_use]
+  pub fn lines(&self) -> Vec<&Line<'a>> {
+    match self {
+      Self::Text(line)
+      | Self::Link(line)
+      | Self::Heading(line)
+      | Self::Blockquote(line)
+      | Self::Whitespace(line) => vec![line],
+      Self::List(items) => items.iter().collect(),
+      Self::PreformattedText { open, body, close } =>
+        std::iter::once(open).chain(body).chain(close.as_ref()).collect(),
+    }
+  }
+
+  /// Lower the `Node` into a [`Node`](borrowed::Node) of an
+  /// [`Ast`](borrowed::Ast)
+  #[must_use]
+  pub fn lower(&self) -> borrowed::Node<'_> {
+    match self {
+      Self::Text(line) => borrowed::Node::Text(Cow::Borrowed(&line.text)),
+      Self::Link(line) => borrowed::Node::Link {
+        to:   Cow::Borrowed(&line.to),
+        text: (!line.text.trim().is_empty()).then(|| {
+          Cow::Owned(line.text.split_whitespace().collect::<Vec<_>>().join(" "))
+        }),
+      },
+      Self::Heading(line) => borrowed::Node::Heading {
+        level: line.marker.chars().take_while(|&c| c == '#').count(),
+        text:  Cow::Borrowed(&line.text),
+      },
+      Self::List(items) => borrowed::Node::List(
+        items.iter().map(|item| Cow::Borrowed(&*item.text)).collect(),
+      ),
+      Self::Blockquote(line) =>
+        borrowed::Node::Blockquote(Cow::Borrowed(&line.text)),
+      Self::PreformattedText { open, body, .. } =>
+        borrowed::Node::PreformattedText {
+          alt_text: (!open.text.is_empty()).then(|| Cow::Borrowed(&*open.text)),
+          text:     Cow::Owned(body.iter().fold(
+            String::new(),
+            |mut text, line| {
+              text.push_str(&line.text);
+              text.push('\n');
+
+              text
+            },
+          )),
+        },
+      Self::Whitespace(_) => borrowed::Node::Whitespace,
+    }
+  }
+}
+
+impl fmt::Display for Node<'_> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    for line in self.lines() {
+      write!(f, "{line}")?;
+    }
+
+    Ok(())
+  }
+}
+
+/// The way a [`Line`] ends
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum LineEnding {
+  /// `\n`
+  Lf,
+  /// `\r\n`
+  CrLf,
+}
+
+impl LineEnding {
+  /// The line ending as it appears in Gemtext
+  #[must_use]
+  pub const fn as_str(&self) -> &'static str {
+    match self {
+      Self::Lf => "\n",
+      Self::CrLf => "\r\n",
+    }
+  }
+}
+
+/// A single line of Gemtext, split into its parts
+///
+/// A line is written out as its marker, spacing, location, separator, text
+/// and line ending, in that order. The parts a line type does not have are
+/// empty, e.g., only link lines have a location and a separator.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Line<'a> {
+  marker:    Cow<'a, str>,
+  spacing:   Cow<'a, str>,
+  to:        Cow<'a, str>,
+  separator: Cow<'a, str>,
+  text:      Cow<'a, str>,
+  ending:    Option<LineEnding>,
+}
+
+impl<'a> Line<'a> {
+  /// Split a line into a marker of `length` bytes, the whitespace after it,
+  /// and its text
+  fn marked(
+    (_, text, ending): (usize, &'a str, Option<LineEnding>),
+    length: usize,
+  ) -> Self {
+    let (marker, rest) = text.split_at(length);
+    let (spacing, text) = split_whitespace(rest);
+
+    Self {
+      marker: Cow::Borrowed(marker),
+      spacing: Cow::Borrowed(spacing),
+      text: Cow::Borrowed(text),
+      ending,
+      ..Self::default()
+    }
+  }
+
+  /// A line which is nothing but text
+  fn plain((_, text, ending): (usize, &'a str, Option<LineEnding>)) -> Self {
+    Self { text: Cow::Borrowed(text), ending, ..Self::default() }
+  }
+
+  /// Split a preformatted toggle line into its toggle and its alt-text
+  fn toggle((_, text, ending): (usize, &'a str, Option<LineEnding>)) -> Self {
+    let marker = text.get(..3).unwrap_or(text);
+
+    Self {
+      marker: Cow::Borrowed(marker),
+      text: Cow::Borrowed(&text[marker.len()..]),
+      ending,
+      ..Self::default()
+    }
+  }
+
+  /// The line identifier, e.g., `=>`, `##` or `*`
+  #[must_use]
+  pub fn marker(&self) -> &str { &self.marker }
+
+  /// Obtain mutable access to the line identifier
+  pub fn marker_mut(&mut self) -> &mut Cow<'a, str> { &mut self.marker }
+
+  /// The whitespace between the line identifier and the rest of the line
+  #[must_use]
+  pub fn spacing(&self) -> &str { &self.spacing }
+
+  /// Obtain mutable access to the whitespace after the line identifier
+  pub fn spacing_mut(&mut self) -> &mut Cow<'a, str> { &mut self.spacing }
+
+  /// The location that a link line is pointing to
+  #[must_use]
+  pub fn to(&self) -> &str { &self.to }
+
+  /// Obtain mutable access to the location of a link line
+  pub fn to_mut(&mut self) -> &mut Cow<'a, str> { &mut self.to }
+
+  /// The whitespace between the location and the text of a link line
+  #[must_use]
+  pub fn separator(&self) -> &str { &self.separator }
+
+  /// Obtain mutable access to the whitespace after a link line's location
+  pub fn separator_mut(&mut self) -> &mut Cow<'a, str> { &mut self.separator }
+
+  /// The text of the line, including any trailing whitespace
+  #[must_use]
+  pub fn text(&self) -> &str { &self.text }
+
+  /// Obtain mutable access to the text of the line
+  pub fn text_mut(&mut self) -> &mut Cow<'a, str> { &mut self.text }
+
+  /// The line ending, which only the last line of a source may lack
+  #[must_use]
+  pub const fn ending(&self) -> Option<LineEnding> { self.ending }
+
+  /// Obtain mutable access to the line ending
+  pub fn ending_mut(&mut self) -> &mut Option<LineEnding> { &mut self.ending }
+}
+
+impl fmt::Display for Line<'_> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(
+      f,
+      "{}{}{}{}{}{}",
+      self.marker,
+      self.spacing,
+      self.to,
+      self.separator,
+      self.text,
+      self.ending.map_or("", |ending| ending.as_str())
+    )
+  }
+}
+
+/// Split the leading whitespace off of `text`
+fn split_whitespace(text: &str) -> (&str, &str) {
+  text.split_at(text.len() - text.trim_start().len())
+}
+
+/// An iterator over the lines of a Gemtext source, yielding the offset, text
+/// and ending of each, splitting lines like the parser does
+struct RawLines<'a> {
+  source: &'a str,
+  offset: usize,
+}
+
+impl<'a> Iterator for RawLines<'a> {
+  type Item = (usize, &'a str, Option<LineEnding>);
+
+  fn next(&mut self) -> Option<Self::Item> {
+    let start = self.offset;
+    let rest = self.source.get(start..).filter(|rest| !rest.is_empty())?;
+    let Some(end) = rest.find('\n') else {
+      self.offset = self.source.len();
+
+      return Some((start, rest, None));
+    };
+
+    self.offset += end + 1;
+
+    Some(
+      rest[..end]
+        .strip_suffix('\r')
+        .map_or((start, &rest[..end], Some(LineEnding::Lf)), |text| {
+          (start, text, Some(LineEnding::CrLf))
+        }),
+    )
+  }
+}
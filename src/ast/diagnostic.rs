@@ -0,0 +1,157 @@
+// This file is part of Germ <https://github.com/gemrest/germ>.
+// Copyright (C) 2022-2022 Fuwn <contact@fuwn.me>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+//
+// Copyright (C) 2022-2022 Fuwn <contact@fuwn.me>
+// SPDX-License-Identifier: GPL-3.0-only
+
+use {super::Span, std::fmt};
+
+/// How serious a [`Diagnostic`] is
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+  /// The Gemtext is valid, but likely not what its author intended
+  Warning,
+  /// The Gemtext is invalid, and had to be recovered from
+  Error,
+}
+
+impl fmt::Display for Severity {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str(match self {
+      Self::Warning => "warning",
+      Self::Error => "error",
+    })
+  }
+}
+
+/// The problems the parser can run into
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[allow(clippy::module_name_repetitions)]
+pub enum DiagnosticKind {
+  /// A link line without a location, which is recovered from as a text line
+  ///
+  /// ```gemini
+  /// =>
+  /// ```
+  EmptyLink,
+  /// A preformatted block without a closing toggle, which is recovered from
+  /// by closing it at the end of the source
+  UnterminatedPreformatted,
+  /// A heading line deeper than `###`, which is kept as is
+  HeadingTooDeep,
+}
+
+impl DiagnosticKind {
+  /// The [`Severity`] of the `DiagnosticKind`
+  #[must_use]
+  pub const fn severity(&self) -> Severity {
+    match self {
+      Self::EmptyLink | Self::UnterminatedPreformatted => Severity::Error,
+      Self::HeadingTooDeep => Severity::Warning,
+    }
+  }
+}
+
+impl fmt::Display for DiagnosticKind {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str(match self {
+      Self::EmptyLink => "link line has no location",
+      Self::UnterminatedPreformatted => "preformatted block is never closed",
+      Self::HeadingTooDeep => "heading is deeper than `###`",
+    })
+  }
+}
+
+/// A problem found while parsing Gemtext, and where it was found
+///
+/// # Example
+///
+/// ```rust
+/// let (_, diagnostics) = germ::ast::Ast::from_str_lenient("=>");
+///
+/// assert_eq!(
+///   diagnostics[0].to_string(),
+///   "1:1: error: link line has no location"
+/// );
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Diagnostic {
+  kind: DiagnosticKind,
+  span: Span,
+}
+
+impl Diagnostic {
+  pub(crate) const fn new(kind: DiagnosticKind, span: Span) -> Self {
+    Self { kind, span }
+  }
+
+  /// What the problem is
+  #[must_use]
+  pub const fn kind(&self) -> DiagnosticKind { self.kind }
+
+  /// How serious the problem is
+  #[must_use]
+  pub const fn severity(&self) -> Severity { self.kind.severity() }
+
+  /// Where in the source the problem is
+  #[must_use]
+  pub const fn span(&self) -> Span { self.span }
+}
+
+impl fmt::Display for Diagnostic {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(
+      f,
+      "{}:{}: {}: {}",
+      self.span.start().line(),
+      self.span.start().column(),
+      self.severity(),
+      self.kind
+    )
+  }
+}
+
+/// The error of [`Ast::try_from_str`](super::Ast::try_from_str), holding every
+/// [`Diagnostic`] of the source, of which at least one is an error
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseErrors {
+  diagnostics: Vec<Diagnostic>,
+}
+
+impl ParseErrors {
+  pub(crate) const fn new(diagnostics: Vec<Diagnostic>) -> Self {
+    Self { diagnostics }
+  }
+
+  /// Every [`Diagnostic`] of the source, in source order
+  #[must_use]
+  pub fn diagnostics(&self) -> &[Diagnostic] { &self.diagnostics }
+}
+
+impl fmt::Display for ParseErrors {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    for (index, diagnostic) in self.diagnostics.iter().enumerate() {
+      if index != 0 {
+        writeln!(f)?;
+      }
+
+      write!(f, "{diagnostic}")?;
+    }
+
+    Ok(())
+  }
+}
+
+impl std::error::Error for ParseErrors {}
@@ -0,0 +1,539 @@
+// This file is part of Germ <https://github.com/gemrest/germ>.
+// Copyright (C) 2022-2022 Fuwn <contact@fuwn.me>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+//
+// Copyright (C) 2022-2022 Fuwn <contact@fuwn.me>
+// SPDX-License-Identifier: GPL-3.0-only
+
+use {
+  super::{Ast, Node},
+  std::fmt,
+};
+
+/// A change to the nodes of an [`Ast`], as found by [`diff`]
+///
+/// The indexes of removed nodes are into the old `Ast`, and the indexes of
+/// added nodes are into the new `Ast`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Operation {
+  /// A node which is only in the new `Ast`
+  Insert {
+    /// The index of the node in the new `Ast`
+    index: usize,
+    /// The inserted node
+    node:  Node,
+  },
+  /// A node which is only in the old `Ast`
+  Delete {
+    /// The index of the node in the old `Ast`
+    index: usize,
+    /// The deleted node
+    node:  Node,
+  },
+  /// A node which was replaced by another node of the same type
+  Modify {
+    /// The index of the node in the old `Ast`
+    old_index: usize,
+    /// The index of the node in the new `Ast`
+    new_index: usize,
+    /// The node before it was modified
+    old:       Node,
+    /// The node after it was modified
+    new:       Node,
+  },
+  /// A node which is in both `Ast`s, but at a different place
+  Move {
+    /// The index of the node in the old `Ast`
+    old_index: usize,
+    /// The index of the node in the new `Ast`
+    new_index: usize,
+    /// The moved node
+    node:      Node,
+  },
+}
+
+/// The changes between two [`Ast`]s, as found by [`diff`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Diff {
+  operations: Vec<Operation>,
+}
+
+/// An operation of a [`Diff`] which did not apply to an [`Ast`], as returned by
+/// [`Diff::apply`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Conflict {
+  operation: usize,
+}
+
+impl Conflict {
+  /// The index of the operation within [`Diff::operations`]
+  #[must_use]
+  pub const fn operation(&self) -> usize { self.operation }
+}
+
+impl fmt::Display for Conflict {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "operation {} of the diff does not apply", self.operation)
+  }
+}
+
+impl std::error::Error for Conflict {}
+
+/// Find the changes between the nodes of two [`Ast`]s
+///
+/// Nodes are matched by a longest common subsequence. An unmatched node which
+/// is equal to an unmatched node of the other `Ast` is a move, and an unmatched
+/// node which takes the place of an unmatched node of the same type is a
+/// modification.
+///
+/// # Example
+///
+/// ```rust
+/// use germ::ast::{Ast, Node, Operation, diff};
+///
+/// let old = Ast::from_string("# Hi\n=> /a A\nText");
+/// let new = Ast::from_string("# Hello\nText\n=> /a A");
+///
+/// assert_eq!(diff(&old, &new).operations(), &[
+///   Operation::Modify {
+///     old_index: 0,
+///     new_index: 0,
+///     old:       Node::Heading { level: 1, text: "Hi".to_string() },
+///     new:       Node::Heading { level: 1, text: "Hello".to_string() },
+///   },
+///   Operation::Move {
+///     old_index: 1,
+///     new_index: 2,
+///     node:      Node::Link {
+///       to:   "/a".to_string(),
+///       text: Some("A".to_string()),
+///     },
+///   },
+/// ]);
+/// ```
+#[must_use]
+pub fn diff(old: &Ast, new: &Ast) -> Diff {
+  let (old, new) = (old.inner(), new.inner());
+  let gaps = gaps(old, new);
+  let mut destinations = vec![None; old.len()];
+  let mut claimed = vec![false; new.len()];
+
+  // An unmatched node is moved rather than deleted if the same node was
+  // inserted anywhere else.
+  for (deleted, _) in &gaps {
+    for &old_index in deleted {
+      if let Some(&new_index) =
+        gaps.iter().flat_map(|(_, inserted)| inserted).find(|&&new_index| {
+          !claimed[new_index] && new[new_index] == old[old_index]
+        })
+      {
+        destinations[old_index] = Some(new_index);
+        claimed[new_index] = true;
+      }
+    }
+  }
+
+  let mut operations = vec![];
+
+  for (deleted, inserted) in gaps {
+    let start = operations.len();
+    let mut unmoved = vec![];
+
+    for old_index in deleted {
+      match destinations.get(old_index).copied().flatten() {
+        Some(new_index) => operations.push(Operation::Move {
+          old_index,
+          new_index,
+          node: old[old_index].clone(),
+        }),
+        None => unmoved.push(old_index),
+      }
+    }
+
+    let deleted = unmoved;
+    let inserted =
+      inserted.into_iter().filter(|&index| !claimed[index]).collect::<Vec<_>>();
+
+    for pair in 0..deleted.len().max(inserted.len()) {
+      match (deleted.get(pair), inserted.get(pair)) {
+        (Some(&old_index), Some(&new_index))
+          if std::mem::discriminant(&old[old_index])
+            == std::mem::discriminant(&new[new_index]) =>
+          operations.push(Operation::Modify {
+            old_index,
+            new_index,
+            old: old[old_index].clone(),
+            new: new[new_index].clone(),
+          }),
+        (old_index, new_index) => {
+          if let Some(&index) = old_index {
+            operations
+              .push(Operation::Delete { index, node: old[index].clone() });
+          }
+
+          if let Some(&index) = new_index {
+            operations
+              .push(Operation::Insert { index, node: new[index].clone() });
+          }
+        }
+      }
+    }
+
+    // Within a gap, the removed nodes come first, in their old order, followed
+    // by the added nodes, in their new order.
+    operations[start..].sort_by_key(|operation| match operation {
+      Operation::Delete { index, .. }
+      | Operation::Modify { old_index: index, .. }
+      | Operation::Move { old_index: index, .. } => (false, *index),
+      Operation::Insert { index, .. } => (true, *index),
+    });
+  }
+
+  Diff { operations }
+}
+
+/// Pair up the runs of unmatched nodes of `old` and `new`, between the nodes
+/// of their longest common subsequence
+fn gaps(old: &[Node], new: &[Node]) -> Vec<(Vec<usize>, Vec<usize>)> {
+  let mut matches = vec![];
+
+  common(old, new, (0, 0), &mut matches);
+  matches.push((old.len(), new.len()));
+
+  let (mut i, mut j) = (0, 0);
+  let mut gaps = vec![];
+
+  for (old_index, new_index) in matches {
+    if i < old_index || j < new_index {
+      gaps.push(((i..old_index).collect(), (j..new_index).collect()));
+    }
+
+    (i, j) = (old_index + 1, new_index + 1);
+  }
+
+  gaps
+}
+
+/// Push the index pairs of a longest common subsequence of `old` and `new`,
+/// offset by `offset`, onto `matches`, in order
+///
+/// The common prefix and suffix are matched as they are, and the rest is split
+/// in two at its [`middle`] and matched piece by piece, which takes space
+/// linear in the number of nodes.
+fn common(
+  old: &[Node],
+  new: &[Node],
+  offset: (usize, usize),
+  matches: &mut Vec<(usize, usize)>,
+) {
+  let prefix = old.iter().zip(new).take_while(|(old, new)| old == new).count();
+  let (old, new) = (&old[prefix..], &new[prefix..]);
+
+  matches.extend((0..prefix).map(|k| (offset.0 + k, offset.1 + k)));
+
+  let suffix = old
+    .iter()
+    .rev()
+    .zip(new.iter().rev())
+    .take_while(|(old, new)| old == new)
+    .count();
+  let (old, new) = (&old[..old.len() - suffix], &new[..new.len() - suffix]);
+  let offset = (offset.0 + prefix, offset.1 + prefix);
+
+  if !old.is_empty() && !new.is_empty() {
+    let (x, y) = middle(old, new);
+
+    common(&old[..x], &new[..y], offset, matches);
+    common(&old[x..], &new[y..], (offset.0 + x, offset.1 + y), matches);
+  }
+
+  matches.extend(
+    (0..suffix).map(|k| (offset.0 + old.len() + k, offset.1 + new.len() + k)),
+  );
+}
+
+/// Find where the middle snake of a shortest edit script from `old` to `new`
+/// splits them, by Myers' linear space algorithm
+///
+/// Neither `old` nor `new` may be empty, and they must differ in their first
+/// and in their last nodes, so that both halves of the split are smaller.
+#[allow(clippy::cast_possible_wrap, clippy::cast_sign_loss)]
+fn middle(old: &[Node], new: &[Node]) -> (usize, usize) {
+  // The length of a slice never exceeds `isize::MAX`, and `x` and `y` stay
+  // within `0..=n` and `0..=m` wherever they index, so the casts are lossless.
+  let (n, m) = (old.len() as isize, new.len() as isize);
+  let delta = n - m;
+  let at = |k: isize| (k + n + m + 1) as usize;
+  // The furthest `x` reached on each diagonal `k`, where `x - y == k`, from the
+  // start forwards, and from the end backwards
+  let mut forward = vec![0; at(n + m + 2)];
+  let mut backward = forward.clone();
+
+  for d in 0..=(n + m + 1) / 2 {
+    // Walking the diagonals with the most removals first prefers removing
+    // nodes of `old` before adding nodes of `new`, where both are as short.
+    for k in (-d..=d).rev().step_by(2) {
+      let mut x =
+        if k == -d || (k != d && forward[at(k - 1)] < forward[at(k + 1)]) {
+          forward[at(k + 1)]
+        } else {
+          forward[at(k - 1)] + 1
+        };
+      let start = (x, x - k);
+
+      while x < n && x - k < m && old[x as usize] == new[(x - k) as usize] {
+        x += 1;
+      }
+
+      forward[at(k)] = x;
+
+      if delta % 2 != 0
+        && (k - delta).abs() < d
+        && x + backward[at(delta - k)] >= n
+      {
+        return (start.0 as usize, start.1 as usize);
+      }
+    }
+
+    for k in (-d..=d).step_by(2) {
+      let mut x =
+        if k == -d || (k != d && backward[at(k - 1)] < backward[at(k + 1)]) {
+          backward[at(k + 1)]
+        } else {
+          backward[at(k - 1)] + 1
+        };
+
+      while x < n
+        && x - k < m
+        && old[(n - x - 1) as usize] == new[(m - x + k - 1) as usize]
+      {
+        x += 1;
+      }
+
+      backward[at(k)] = x;
+
+      if delta % 2 == 0
+        && (delta - k).abs() <= d
+        && x + forward[at(delta - k)] >= n
+      {
+        return ((n - x) as usize, (m - x + k) as usize);
+      }
+    }
+  }
+
+  (old.len(), new.len())
+}
+
+impl Diff {
+  /// The operations of the `Diff`, in document order
+  #[must_use]
+  pub fn operations(&self) -> &[Operation] { &self.operations }
+
+  /// Whether the two `Ast`s were equal
+  #[must_use]
+  pub fn is_empty(&self) -> bool { self.operations.is_empty() }
+
+  /// Apply the `Diff` as a patch to the old `Ast`, giving the new `Ast`
+  ///
+  /// The nodes which the `Diff` does not touch are kept, in order, in the
+  /// places which the `Diff` leaves free.
+  ///
+  /// # Example
+  ///
+  /// ```rust
+  /// use germ::ast::{Ast, diff};
+  ///
+  /// let old = Ast::from_string("# Hi\n=> /a A\nText");
+  /// let new = Ast::from_string("# Hello\nText\n=> /b B");
+  ///
+  /// assert_eq!(diff(&old, &new).apply(&old), Ok(new));
+  /// ```
+  ///
+  /// # Errors
+  ///
+  /// - Errors if a node which the `Diff` removes, modifies, or moves is not in
+  ///   the `Ast` at its old index, or if the `Diff` adds two nodes at the same
+  ///   index
+  pub fn apply(&self, ast: &Ast) -> Result<Ast, Conflict> {
+    let old = ast.inner();
+    let (mut removed, mut added) = (0, 0);
+
+    for operation in &self.operations {
+      match operation {
+        Operation::Insert { .. } => added += 1,
+        Operation::Delete { .. } => removed += 1,
+        Operation::Modify { .. } | Operation::Move { .. } => {}
+      }
+    }
+
+    // Every removal is checked against `ast` below, so only a conflicting
+    // `Diff` could remove more nodes than there are.
+    let length = (old.len() + added).saturating_sub(removed);
+    let mut taken = vec![false; old.len()];
+    let mut nodes = vec![None; length];
+
+    for (operation_index, operation) in self.operations.iter().enumerate() {
+      let (old_index, expected, new_index, node) = match operation {
+        Operation::Insert { index, node } => (None, None, Some(*index), node),
+        Operation::Delete { index, node } =>
+          (Some(*index), Some(node), None, node),
+        Operation::Modify { old_index, new_index, old, new } =>
+          (Some(*old_index), Some(old), Some(*new_index), new),
+        Operation::Move { old_index, new_index, node } =>
+          (Some(*old_index), Some(node), Some(*new_index), node),
+      };
+
+      if let Some(old_index) = old_index {
+        if old.get(old_index) != expected || taken[old_index] {
+          return Err(Conflict { operation: operation_index });
+        }
+
+        taken[old_index] = true;
+      }
+
+      if let Some(new_index) = new_index {
+        match nodes.get_mut(new_index) {
+          Some(slot @ None) => *slot = Some(node.clone()),
+          _ => return Err(Conflict { operation: operation_index }),
+        }
+      }
+    }
+
+    let mut kept = old
+      .iter()
+      .zip(taken)
+      .filter_map(|(node, taken)| (!taken).then_some(node));
+
+    Ok(
+      nodes
+        .into_iter()
+        .filter_map(|node| node.or_else(|| kept.next().cloned()))
+        .collect(),
+    )
+  }
+
+  /// Emit the `Diff` as a Gemtext patch
+  ///
+  /// Each operation is a hunk headed by the node indexes it applies to, and
+  /// followed by the Gemtext lines of its nodes: `-` for removed lines, `+`
+  /// for added lines, and ` ` for moved lines.
+  ///
+  /// # Example
+  ///
+  /// ```rust
+  /// use germ::ast::{Ast, diff};
+  ///
+  /// let old = Ast::from_string("# Hi\n=> /a A\nText");
+  /// let new = Ast::from_string("# Hello\nText\n=> /a A\n* 1\n* 2");
+  ///
+  /// assert_eq!(
+  ///   diff(&old, &new).to_gemtext(),
+  ///   "@@ -0 +0 @@\n-# Hi\n+# Hello\n@@ -1 +2 @@\n => /a A\n@@ +3 @@\n+* 1\n+* \
+  ///    2\n"
+  /// );
+  /// ```
+  #[must_use]
+  pub fn to_gemtext(&self) -> String {
+    fn lines(patch: &mut String, prefix: char, node: &Node) {
+      for line in node.to_gemtext().split('\n') {
+        patch.push(prefix);
+        patch.push_str(line);
+        patch.push('\n');
+      }
+    }
+
+    let mut patch = String::new();
+
+    for operation in &self.operations {
+      match operation {
+        Operation::Insert { index, node } => {
+          patch.push_str(&format!("@@ +{index} @@\n"));
+          lines(&mut patch, '+', node);
+        }
+        Operation::Delete { index, node } => {
+          patch.push_str(&format!("@@ -{index} @@\n"));
+          lines(&mut patch, '-', node);
+        }
+        Operation::Modify { old_index, new_index, old, new } => {
+          patch.push_str(&format!("@@ -{old_index} +{new_index} @@\n"));
+          lines(&mut patch, '-', old);
+          lines(&mut patch, '+', new);
+        }
+        Operation::Move { old_index, new_index, node } => {
+          patch.push_str(&format!("@@ -{old_index} +{new_index} @@\n"));
+          lines(&mut patch, ' ', node);
+        }
+      }
+    }
+
+    patch
+  }
+
+  /// Emit the `Diff` as HTML, with removed nodes in `<del>` elements, and
+  /// added nodes in `<ins>` elements
+  ///
+  /// Moved nodes are both removed and added, with a class of `moved`.
+  ///
+  /// # Example
+  ///
+  /// ```rust
+  /// use germ::ast::{Ast, diff};
+  ///
+  /// let old = Ast::from_string("# Hi\nText");
+  /// let new = Ast::from_string("# Hello\nText\n=> /a A");
+  ///
+  /// assert_eq!(
+  ///   diff(&old, &new).to_html(),
+  ///   "<del><h1>Hi</h1></del><ins><h1>Hello</h1></ins><ins><a \
+  ///    href=\"/a\">A</a><br></ins>"
+  /// );
+  /// ```
+  #[cfg(feature = "convert")]
+  #[must_use]
+  pub fn to_html(&self) -> String {
+    // Nodes are converted one at a time, so their headings would not get
+    // unique ids.
+    fn html(node: &Node) -> String {
+      crate::convert::HtmlOptions::new()
+        .heading_ids(false)
+        .to_html(&Ast::from_nodes(vec![node.clone()]))
+    }
+
+    let mut html_diff = String::new();
+
+    for operation in &self.operations {
+      match operation {
+        Operation::Insert { node, .. } =>
+          html_diff.push_str(&format!("<ins>{}</ins>", html(node))),
+        Operation::Delete { node, .. } =>
+          html_diff.push_str(&format!("<del>{}</del>", html(node))),
+        Operation::Modify { old, new, .. } => html_diff.push_str(&format!(
+          "<del>{}</del><ins>{}</ins>",
+          html(old),
+          html(new)
+        )),
+        Operation::Move { node, .. } => {
+          let node = html(node);
+
+          html_diff.push_str(&format!(
+            "<del class=\"moved\">{node}</del><ins \
+             class=\"moved\">{node}</ins>"
+          ));
+        }
+      }
+    }
+
+    html_diff
+  }
+}
@@ -0,0 +1,98 @@
+// This file is part of Germ <https://github.com/gemrest/germ>.
+// Copyright (C) 2022-2022 Fuwn <contact@fuwn.me>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+//
+// Copyright (C) 2022-2022 Fuwn <contact@fuwn.me>
+// SPDX-License-Identifier: GPL-3.0-only
+
+use super::Node;
+
+/// Rewrite the nodes of an AST tree, one default method per node type
+///
+/// Every method hands back its node unchanged by default, so a folder only
+/// has to implement the methods of the node types it rewrites. Returning
+/// `None` drops the node from the AST tree.
+///
+/// # Example
+///
+/// ```rust
+/// use germ::ast::{Ast, Fold, Node};
+///
+/// struct Tidy;
+///
+/// impl Fold for Tidy {
+///   fn fold_heading(&mut self, level: usize, text: String) -> Option<Node> {
+///     Some(Node::Heading { level: (level + 1).min(3), text })
+///   }
+///
+///   fn fold_whitespace(&mut self) -> Option<Node> { None }
+/// }
+///
+/// assert_eq!(
+///   Ast::from_string("# Hi\n\n## There").fold(&mut Tidy).to_gemtext(),
+///   "## Hi\n### There"
+/// );
+/// ```
+pub trait Fold {
+  /// Fold any node, dispatching to the method of its node type
+  fn fold_node(&mut self, node: Node) -> Option<Node> {
+    match node {
+      Node::Text(text) => self.fold_text(text),
+      Node::Link { to, text } => self.fold_link(to, text),
+      Node::Heading { level, text } => self.fold_heading(level, text),
+      Node::List(items) => self.fold_list(items),
+      Node::Blockquote(text) => self.fold_blockquote(text),
+      Node::PreformattedText { alt_text, text } =>
+        self.fold_preformatted_text(alt_text, text),
+      Node::Whitespace => self.fold_whitespace(),
+    }
+  }
+
+  /// Fold a text line
+  fn fold_text(&mut self, text: String) -> Option<Node> {
+    Some(Node::Text(text))
+  }
+
+  /// Fold a link line
+  fn fold_link(&mut self, to: String, text: Option<String>) -> Option<Node> {
+    Some(Node::Link { to, text })
+  }
+
+  /// Fold a heading line
+  fn fold_heading(&mut self, level: usize, text: String) -> Option<Node> {
+    Some(Node::Heading { level, text })
+  }
+
+  /// Fold a collection of sequential list item lines
+  fn fold_list(&mut self, items: Vec<String>) -> Option<Node> {
+    Some(Node::List(items))
+  }
+
+  /// Fold a blockquote line
+  fn fold_blockquote(&mut self, text: String) -> Option<Node> {
+    Some(Node::Blockquote(text))
+  }
+
+  /// Fold a preformatted block
+  fn fold_preformatted_text(
+    &mut self,
+    alt_text: Option<String>,
+    text: String,
+  ) -> Option<Node> {
+    Some(Node::PreformattedText { alt_text, text })
+  }
+
+  /// Fold a whitespace line
+  fn fold_whitespace(&mut self) -> Option<Node> { Some(Node::Whitespace) }
+}
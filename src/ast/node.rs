@@ -16,6 +16,8 @@
 // Copyright (C) 2022-2022 Fuwn <contact@fuwn.me>
 // SPDX-License-Identifier: GPL-3.0-only
 
+use std::borrow::Cow;
+
 /// A Gemtext AST node.
 ///
 /// Each Gemtext line is a `Node`, and some lines can even be grouped together,
@@ -26,7 +28,27 @@
 /// - [Gemtext Documentation](https://gemini.circumlunar.space/docs/gemtext.gmi)
 /// - [Gemtext Cheatsheet](https://gemini.circumlunar.space/docs/cheatsheet.gmi).
 /// - [Gemini Specification](https://gemini.circumlunar.space/docs/specification.gmi).
+///
+/// # Serialisation
+///
+/// With the `serde` feature, a `Node` is (de)serialised as an object tagged by
+/// its `type`, with its fields under `value`:
+///
+/// ```json
+/// {"type":"text","value":"This is a text line"}
+/// {"type":"link","value":{"to":"/","text":"Home"}}
+/// {"type":"heading","value":{"level":1,"text":"Hi"}}
+/// {"type":"list","value":["One","Two"]}
+/// {"type":"blockquote","value":"Quote"}
+/// {"type":"preformatted_text","value":{"alt_text":null,"text":"Pre\n"}}
+/// {"type":"whitespace"}
+/// ```
 #[derive(Debug, PartialEq, Clone, Eq)]
+#[cfg_attr(
+  feature = "serde",
+  derive(serde::Serialize, serde::Deserialize),
+  serde(tag = "type", content = "value", rename_all = "snake_case")
+)]
 pub enum Node {
   /// A text line
   ///
@@ -179,3 +201,25 @@ impl Node {
     super::Ast::from_nodes(vec![self.to_owned()]).to_gemtext()
   }
 }
+
+impl From<super::borrowed::Node<'_>> for Node {
+  fn from(node: super::borrowed::Node<'_>) -> Self {
+    use super::borrowed::Node as Borrowed;
+
+    match node {
+      Borrowed::Text(text) => Self::Text(text.into_owned()),
+      Borrowed::Link { to, text } =>
+        Self::Link { to: to.into_owned(), text: text.map(Cow::into_owned) },
+      Borrowed::Heading { level, text } =>
+        Self::Heading { level, text: text.into_owned() },
+      Borrowed::List(items) =>
+        Self::List(items.into_iter().map(Cow::into_owned).collect()),
+      Borrowed::Blockquote(text) => Self::Blockquote(text.into_owned()),
+      Borrowed::PreformattedText { alt_text, text } => Self::PreformattedText {
+        alt_text: alt_text.map(Cow::into_owned),
+        text:     text.into_owned(),
+      },
+      Borrowed::Whitespace => Self::Whitespace,
+    }
+  }
+}
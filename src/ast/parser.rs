@@ -0,0 +1,692 @@
+// This file is part of Germ <https://github.com/gemrest/germ>.
+// Copyright (C) 2022-2022 Fuwn <contact@fuwn.me>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+//
+// Copyright (C) 2022-2022 Fuwn <contact@fuwn.me>
+// SPDX-License-Identifier: GPL-3.0-only
+
+use {
+  super::{
+    borrowed::Node, Diagnostic, DiagnosticKind, NodeSpan, Position, Span,
+  },
+  std::{borrow::Cow, collections::VecDeque, io::BufRead},
+};
+
+/// Parse a Gemtext `source` into its nodes, their spans and the diagnostics
+/// of the source, borrowing from `source` wherever possible
+pub fn parse(source: &str) -> (Vec<Node<'_>>, Vec<NodeSpan>, Vec<Diagnostic>) {
+  let mut ast = vec![];
+  let mut machine = Machine::new(Some(source));
+
+  // Iterate over all lines in the Gemtext `source`, evaluating each and
+  // appending the AST nodes they complete to the `ast` tree
+  for line in Lines::new(source) {
+    machine.line(line, &mut ast);
+  }
+
+  machine.finish(&mut ast);
+
+  let (nodes, spans) = ast.into_iter().unzip();
+
+  (nodes, spans, machine.diagnostics)
+}
+
+/// Parse a Gemtext `source` into its nodes and their spans, starting from the
+/// line which starts at `start`
+///
+/// Parsing stops early at the start of a line once no node is left open, if
+/// `resync` accepts the position of that line, which is then returned.
+pub fn parse_from(
+  source: &str,
+  start: Position,
+  mut resync: impl FnMut(Position) -> bool,
+) -> (Vec<(Node<'_>, NodeSpan)>, Option<Position>) {
+  let mut nodes = vec![];
+  let mut machine = Machine::new(Some(source));
+  let lines = Lines { source, number: start.line(), offset: start.offset() };
+
+  machine.end = start;
+
+  for line in lines {
+    let next = line.next_position();
+
+    machine.line(line, &mut nodes);
+
+    if matches!(machine.state, State::Line)
+      && next.offset() < source.len()
+      && resync(next)
+    {
+      return (nodes, Some(next));
+    }
+  }
+
+  machine.finish(&mut nodes);
+
+  (nodes, None)
+}
+
+/// A pull-based Gemtext parser which reads its source one line at a time
+///
+/// Only the line being read, and the node it belongs to, are held in memory,
+/// so multi-megabyte documents can be parsed as they arrive. The nodes
+/// produced are identical to those of [`Ast::from_value`](super::Ast).
+///
+/// # Example
+///
+/// ```rust
+/// use germ::ast::{Ast, Node, Parser};
+///
+/// let nodes = Parser::new("# Hi\n=> / Home".as_bytes())
+///   .collect::<std::io::Result<Vec<Node>>>()
+///   .unwrap();
+///
+/// assert_eq!(&nodes, Ast::from_string("# Hi\n=> / Home").inner());
+/// ```
+#[derive(Debug)]
+pub struct Parser<R> {
+  reader:  R,
+  machine: Machine<'static>,
+  nodes:   VecDeque<(Node<'static>, NodeSpan)>,
+  buffer:  String,
+  number:  usize,
+  offset:  usize,
+  done:    bool,
+}
+
+impl<R> Parser<R> {
+  /// Create a `Parser` which reads Gemtext from `reader`
+  pub const fn new(reader: R) -> Self {
+    Self {
+      reader,
+      machine: Machine::new(None),
+      nodes: VecDeque::new(),
+      buffer: String::new(),
+      number: 1,
+      offset: 0,
+      done: false,
+    }
+  }
+
+  /// Consume the `Parser`, returning its reader
+  pub fn into_inner(self) -> R { self.reader }
+
+  /// The [`Diagnostic`]s of the lines which have been read so far
+  ///
+  /// Problems are recovered from like [`Ast::from_str_lenient`] does, so
+  /// the `Parser` never fails on invalid Gemtext.
+  ///
+  /// [`Ast::from_str_lenient`]: super::Ast::from_str_lenient
+  pub fn diagnostics(&self) -> &[Diagnostic] { &self.machine.diagnostics }
+
+  fn pop(&mut self) -> Option<super::Node> {
+    self.nodes.pop_front().map(|(node, _)| node.into_owned())
+  }
+
+  /// Evaluate the `length` bytes which were just read into the buffer, where
+  /// nothing being read marks the end of the source
+  fn feed(&mut self, length: usize) {
+    let mut nodes = vec![];
+
+    if length == 0 {
+      self.done = true;
+
+      self.machine.finish(&mut nodes);
+    } else {
+      let mut text = std::mem::take(&mut self.buffer);
+
+      if text.ends_with('\n') {
+        text.pop();
+
+        if text.ends_with('\r') {
+          text.pop();
+        }
+      }
+
+      self.machine.line(
+        Line {
+          text:   Cow::Owned(text),
+          number: self.number,
+          offset: self.offset,
+          next:   self.offset + length,
+        },
+        &mut nodes,
+      );
+
+      self.number += 1;
+      self.offset += length;
+    }
+
+    self.nodes.extend(nodes);
+  }
+}
+
+impl<R: BufRead> Iterator for Parser<R> {
+  type Item = std::io::Result<super::Node>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    loop {
+      if let Some(node) = self.pop() {
+        return Some(Ok(node));
+      }
+
+      if self.done {
+        return None;
+      }
+
+      match self.reader.read_line(&mut self.buffer) {
+        Ok(length) => self.feed(length),
+        Err(error) => {
+          self.done = true;
+
+          return Some(Err(error));
+        }
+      }
+    }
+  }
+}
+
+#[cfg(feature = "request")]
+impl<R: tokio::io::AsyncBufRead + Unpin + Send> Parser<R> {
+  /// Read the next [`Node`](super::Node) from an asynchronous reader, or
+  /// `None` once the source has been exhausted
+  ///
+  /// # Example
+  ///
+  /// ```rust
+  /// #[tokio::main]
+  /// async fn main() {
+  ///   let mut parser = germ::ast::Parser::new("# Hi\n=> / Home".as_bytes());
+  ///
+  ///   while let Some(node) = parser.next_async().await {
+  ///     println!("{:?}", node.unwrap());
+  ///   }
+  /// }
+  /// ```
+  ///
+  /// # Errors
+  ///
+  /// - May error if the read fails, or if the source is not valid UTF-8
+  pub async fn next_async(&mut self) -> Option<std::io::Result<super::Node>> {
+    loop {
+      if let Some(node) = self.pop() {
+        return Some(Ok(node));
+      }
+
+      if self.done {
+        return None;
+      }
+
+      match tokio::io::AsyncBufReadExt::read_line(
+        &mut self.reader,
+        &mut self.buffer,
+      )
+      .await
+      {
+        Ok(length) => self.feed(length),
+        Err(error) => {
+          self.done = true;
+
+          return Some(Err(error));
+        }
+      }
+    }
+  }
+}
+
+/// The multi-line context the parser is in
+#[derive(Debug)]
+enum State<'a> {
+  /// Each line is a node of its own
+  Line,
+  /// Sequential list item lines are being collected into one list
+  List {
+    items: Vec<Cow<'a, str>>,
+    spans: Vec<Span>,
+    start: Position,
+    end:   Position,
+  },
+  /// Lines are being collected into a preformatted block, up to the closing
+  /// toggle
+  Preformatted {
+    alt_text:   Option<(Cow<'a, str>, Span)>,
+    start:      Position,
+    text_start: Position,
+    text:       String,
+  },
+}
+
+/// The Gemtext state machine, which is fed one line at a time and hands back
+/// nodes as they are completed
+///
+/// When the whole `source` is known up front, the content of preformatted
+/// blocks is sliced out of it instead of being collected line by line.
+#[derive(Debug)]
+pub struct Machine<'a> {
+  source:          Option<&'a str>,
+  state:           State<'a>,
+  whitespace:      Option<bool>,
+  end:             Position,
+  newline:         bool,
+  pub diagnostics: Vec<Diagnostic>,
+}
+
+impl<'a> Machine<'a> {
+  pub const fn new(source: Option<&'a str>) -> Self {
+    Self {
+      source,
+      state: State::Line,
+      whitespace: None,
+      end: Position::new(1, 1, 0),
+      newline: false,
+      diagnostics: Vec::new(),
+    }
+  }
+
+  /// Evaluate a Gemtext line, appending any nodes it completes to `nodes`
+  #[allow(clippy::too_many_lines)]
+  pub fn line(
+    &mut self,
+    line: Line<'a>,
+    nodes: &mut Vec<(Node<'a>, NodeSpan)>,
+  ) {
+    let first = line.text.get(0..1).unwrap_or("");
+
+    // Keep track of where the source ends, as seen so far
+    self.newline = line.next > line.offset + line.text.len();
+    self.end = if self.newline {
+      line.next_position()
+    } else {
+      line.position(line.text.len())
+    };
+
+    if let State::Preformatted { text, .. } = &mut self.state {
+      // If the Gemtext line starts with a backtick, it's a preformatted
+      // toggle, so let's leave the preformatted text context. Otherwise, the
+      // line is a part of the preformatted blocks content.
+      if first == "`" {
+        self.close_preformatted(
+          line.position(line.text.len()),
+          line.position(0),
+          nodes,
+        );
+      } else if self.source.is_none() {
+        text.push_str(&line.text);
+        text.push('\n');
+      }
+
+      return;
+    }
+
+    if first == "*" {
+      // If the Gemtext line starts with an asterisk, it is a list item, so
+      // let's enter a list context, or stay in the one we are already in.
+      let text_start = line.trimmed_start(1);
+      let item = line.slice(text_start..line.text.len());
+      let span = line.span(text_start..line.text.len());
+      let end = line.position(line.text.len());
+
+      if let State::List { items, spans, end: list_end, .. } = &mut self.state {
+        items.push(item);
+        spans.push(span);
+
+        *list_end = end;
+      } else {
+        self.state = State::List {
+          items: vec![item],
+          spans: vec![span],
+          start: line.position(0),
+          end,
+        };
+      }
+
+      return;
+    }
+
+    // If we are in a list context and hit any other line, that must mean that
+    // the list is over, so let's stop adding items to the list context.
+    self.close_list(nodes);
+
+    // Match the first character of the Gemtext line to understand the line
+    // type
+    match first {
+      "=" if line.text.get(2..).map_or(true, |rest| rest.trim().is_empty()) => {
+        // If the link line has no location, there's nothing to link to, so
+        // let's treat it as a text line instead.
+        self.diagnostics.push(Diagnostic::new(
+          DiagnosticKind::EmptyLink,
+          line.span(0..line.text.len()),
+        ));
+        self.text(line, nodes);
+      }
+      "=" => {
+        // If the Gemtext line starts with an "=" ("=>"), it is a link line,
+        // so splitting it up should be easy enough.
+        let rest = line.text.get(2..).unwrap_or("");
+        let words = words(rest);
+        let (to_start, to) = words[0];
+        let mut span = NodeSpan::new(line.span(0..line.text.len()))
+          .with_to(line.span(2 + to_start..2 + to_start + to.len()));
+        let mut text = None;
+
+        if let (Some((text_start, _)), Some((text_end, last))) =
+          (words.get(1), words.last())
+        {
+          let text_end = text_end + last.len();
+
+          span = span.with_text(line.span(2 + text_start..2 + text_end));
+          // The words of the link text are joined by single spaces, so only
+          // allocate if the source separates them any other way.
+          text = Some(
+            if words[1..].windows(2).all(|pair| {
+              rest.get(pair[0].0 + pair[0].1.len()..pair[1].0) == Some(" ")
+            }) {
+              line.slice(2 + text_start..2 + text_end)
+            } else {
+              Cow::Owned(
+                words[1..]
+                  .iter()
+                  .map(|(_, word)| *word)
+                  .collect::<Vec<&str>>()
+                  .join(" "),
+              )
+            },
+          );
+        }
+
+        let to = line.slice(2 + to_start..2 + to_start + to.len());
+
+        self.push(Node::Link { to, text }, span, nodes);
+      }
+      "#" => {
+        // If the Gemtext line starts with an "#", it is a heading, so let's
+        // find out how deep it goes.
+        let level = line.text.chars().take_while(|&c| c == '#').count();
+        // Here, we are skipping the `level` line identifier characters, then
+        // trimming the start, which effectively clips off the line
+        // identifier.
+        let text_start = line.trimmed_start(level);
+
+        if level > 3 {
+          self.diagnostics.push(Diagnostic::new(
+            DiagnosticKind::HeadingTooDeep,
+            line.span(0..level),
+          ));
+        }
+
+        self.push(
+          Node::Heading {
+            level,
+            text: line.slice(text_start..line.text.len()),
+          },
+          NodeSpan::new(line.span(0..line.text.len()))
+            .with_text(line.span(text_start..line.text.len())),
+          nodes,
+        );
+      }
+      ">" => {
+        // If the Gemtext line starts with an ">", it is a blockquote, so
+        // let's just clip off the line identifier.
+        let text_start = line.trimmed_start(1);
+
+        self.push(
+          Node::Blockquote(line.slice(text_start..line.text.len())),
+          NodeSpan::new(line.span(0..line.text.len()))
+            .with_text(line.span(text_start..line.text.len())),
+          nodes,
+        );
+      }
+      "`" => {
+        // If the Gemtext line starts with a backtick, it's a preformatted
+        // toggle, so let's enter a preformatted text context.
+        let alt_text =
+          line.text.get(3..).filter(|alt_text| !alt_text.is_empty()).map(
+            |_| (line.slice(3..line.text.len()), line.span(3..line.text.len())),
+          );
+
+        self.state = State::Preformatted {
+          alt_text,
+          start: line.position(0),
+          text_start: line.next_position(),
+          text: String::new(),
+        };
+      }
+      _ => self.text(line, nodes),
+    }
+  }
+
+  /// Complete the nodes which are still open once there are no more lines
+  pub fn finish(&mut self, nodes: &mut Vec<(Node<'a>, NodeSpan)>) {
+    self.close_list(nodes);
+
+    // If the source ends in a preformatted text context, the closing toggle
+    // is missing, so let's close the preformatted block at the end of the
+    // source.
+    if let State::Preformatted { start, .. } = &self.state {
+      self.diagnostics.push(Diagnostic::new(
+        DiagnosticKind::UnterminatedPreformatted,
+        Span::new(*start, self.end),
+      ));
+      self.close_preformatted(self.end, self.end, nodes);
+    }
+
+    // A source which ends with a newline ends with a whitespace line, even if
+    // the newline was only the line ending of its last line.
+    if self.newline && self.whitespace == Some(false) {
+      self.push(
+        Node::Whitespace,
+        NodeSpan::new(Span::new(self.end, self.end)),
+        nodes,
+      );
+    }
+  }
+
+  /// Evaluate a line which is nothing but text
+  fn text(&mut self, line: Line<'a>, nodes: &mut Vec<(Node<'a>, NodeSpan)>) {
+    let span = NodeSpan::new(line.span(0..line.text.len()));
+
+    if line.text.is_empty() {
+      // If the line has nothing on it, it is a whitespace line, as long as we
+      // aren't in a preformatted line context.
+      self.push(Node::Whitespace, span, nodes);
+    } else {
+      let span = span.with_text(line.span(0..line.text.len()));
+
+      self.push(Node::Text(line.text), span, nodes);
+    }
+  }
+
+  fn push(
+    &mut self,
+    node: Node<'a>,
+    span: NodeSpan,
+    nodes: &mut Vec<(Node<'a>, NodeSpan)>,
+  ) {
+    self.whitespace = Some(matches!(node, Node::Whitespace));
+
+    nodes.push((node, span));
+  }
+
+  fn close_list(&mut self, nodes: &mut Vec<(Node<'a>, NodeSpan)>) {
+    if !matches!(self.state, State::List { .. }) {
+      return;
+    }
+
+    if let State::List { items, spans, start, end } =
+      std::mem::replace(&mut self.state, State::Line)
+    {
+      self.push(
+        Node::List(items),
+        NodeSpan::new(Span::new(start, end)).with_items(spans),
+        nodes,
+      );
+    }
+  }
+
+  /// Complete the preformatted block, which ends at `end` and whose content
+  /// ends at `text_end`
+  fn close_preformatted(
+    &mut self,
+    end: Position,
+    text_end: Position,
+    nodes: &mut Vec<(Node<'a>, NodeSpan)>,
+  ) {
+    if let State::Preformatted { alt_text, start, text_start, text } =
+      std::mem::replace(&mut self.state, State::Line)
+    {
+      let text_start = text_start.min(text_end);
+      let mut span = NodeSpan::new(Span::new(start, end))
+        .with_text(Span::new(text_start, text_end));
+      // The content of a preformatted block runs up to the closing toggle, and
+      // only has to be copied to normalise its line endings.
+      let text = self.source.map_or(Cow::Owned(text), |source| {
+        source.get(text_start.offset()..text_end.offset()).map_or(
+          Cow::Borrowed(""),
+          |text| {
+            let mut text = if text.contains('\r') {
+              Cow::Owned(text.replace("\r\n", "\n"))
+            } else {
+              Cow::Borrowed(text)
+            };
+
+            if !text.is_empty() && !text.ends_with('\n') {
+              text.to_mut().push('\n');
+            }
+
+            text
+          },
+        )
+      });
+
+      if let Some((_, alt_text_span)) = &alt_text {
+        span = span.with_alt_text(*alt_text_span);
+      }
+
+      self.push(
+        Node::PreformattedText {
+          alt_text: alt_text.map(|(alt_text, _)| alt_text),
+          text,
+        },
+        span,
+        nodes,
+      );
+    }
+  }
+}
+
+/// A single line of a Gemtext source, without its line ending
+#[derive(Debug)]
+pub struct Line<'a> {
+  text:   Cow<'a, str>,
+  number: usize,
+  offset: usize,
+  next:   usize,
+}
+
+impl<'a> Line<'a> {
+  /// The position of the byte `index` of the line
+  fn position(&self, index: usize) -> Position {
+    Position::new(
+      self.number,
+      self.text.get(..index).map_or(index, |text| text.chars().count()) + 1,
+      self.offset + index,
+    )
+  }
+
+  /// The position at the start of the following line
+  const fn next_position(&self) -> Position {
+    Position::new(self.number + 1, 1, self.next)
+  }
+
+  fn span(&self, range: std::ops::Range<usize>) -> Span {
+    Span::new(self.position(range.start), self.position(range.end))
+  }
+
+  /// The text of `range` of the line, which is only copied if the line does
+  /// not borrow from the source
+  fn slice(&self, range: std::ops::Range<usize>) -> Cow<'a, str> {
+    match &self.text {
+      Cow::Borrowed(text) => Cow::Borrowed(text.get(range).unwrap_or("")),
+      Cow::Owned(text) => Cow::Owned(text.get(range).unwrap_or("").to_string()),
+    }
+  }
+
+  /// The index of the first non-whitespace character after the first `skip`
+  /// bytes of the line
+  fn trimmed_start(&self, skip: usize) -> usize {
+    self
+      .text
+      .get(skip..)
+      .map_or(self.text.len(), |rest| self.text.len() - rest.trim_start().len())
+  }
+}
+
+/// An iterator over the [`Line`]s of a Gemtext source which keeps track of
+/// where each line is, splitting lines like [`str::lines`]
+struct Lines<'a> {
+  source: &'a str,
+  number: usize,
+  offset: usize,
+}
+
+impl<'a> Lines<'a> {
+  const fn new(source: &'a str) -> Self {
+    Self { source, number: 1, offset: 0 }
+  }
+}
+
+impl<'a> Iterator for Lines<'a> {
+  type Item = Line<'a>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    let rest =
+      self.source.get(self.offset..).filter(|rest| !rest.is_empty())?;
+    let (text, length) = rest.find('\n').map_or((rest, rest.len()), |end| {
+      (rest[..end].strip_suffix('\r').unwrap_or(&rest[..end]), end + 1)
+    });
+    let line = Line {
+      text:   Cow::Borrowed(text),
+      number: self.number,
+      offset: self.offset,
+      next:   self.offset + length,
+    };
+
+    self.number += 1;
+    self.offset += length;
+
+    Some(line)
+  }
+}
+
+/// Split `text` on whitespace like [`str::split_whitespace`], keeping the byte
+/// index of each word
+fn words(text: &str) -> Vec<(usize, &str)> {
+  let mut words = vec![];
+  let mut start = None;
+
+  for (index, character) in text.char_indices() {
+    if character.is_whitespace() {
+      if let Some(start) = start.take() {
+        words.push((start, &text[start..index]));
+      }
+    } else if start.is_none() {
+      start = Some(index);
+    }
+  }
+
+  if let Some(start) = start {
+    words.push((start, &text[start..]));
+  }
+
+  words
+}
@@ -0,0 +1,138 @@
+// This file is part of Germ <https://github.com/gemrest/germ>.
+// Copyright (C) 2022-2022 Fuwn <contact@fuwn.me>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+//
+// Copyright (C) 2022-2022 Fuwn <contact@fuwn.me>
+// SPDX-License-Identifier: GPL-3.0-only
+
+use {
+  super::{parser, Ast, Node, Position},
+  std::ops::Range,
+};
+
+impl Ast {
+  /// Re-parse `Ast` after an edit of its source, reusing the nodes which the
+  /// edit did not touch
+  ///
+  /// `edit` is the byte range of the old source which was replaced by
+  /// `replacement`, and `source` is the new source. Parsing restarts at the
+  /// node before the edited one, so that a list or preformatted block the edit
+  /// joins or splits is re-derived, and stops as soon as it lines up with an
+  /// old node after the edit again.
+  ///
+  /// The new `Ast` is returned along with the index range of its nodes which
+  /// differ from the old ones. An `Ast` without spans, e.g., one built by
+  /// [`Ast::from_nodes`], is parsed in full.
+  ///
+  /// # Example
+  ///
+  /// ```rust
+  /// use germ::ast::{Ast, Node};
+  ///
+  /// let ast = Ast::from_string("# Hi\n* a\nText\n=> / Home");
+  /// let (ast, changed) =
+  ///   ast.reparse(5..5, "* b\n", "# Hi\n* b\n* a\nText\n=> / Home");
+  ///
+  /// assert_eq!(changed, 1..2);
+  /// assert_eq!(ast[1], Node::List(vec!["b".to_string(), "a".to_string()]));
+  /// assert_eq!(ast.spans()[3].span().start().line(), 5);
+  /// ```
+  #[must_use]
+  pub fn reparse(
+    &self,
+    edit: Range<usize>,
+    replacement: &str,
+    source: &str,
+  ) -> (Self, Range<usize>) {
+    if self.spans().len() != self.len() {
+      let ast = Self::from_value(source);
+      let changed = 0..ast.len();
+
+      return (ast, changed);
+    }
+
+    let starts =
+      self.spans().iter().map(|span| span.span().start()).collect::<Vec<_>>();
+    let edited = starts
+      .partition_point(|start| start.offset() <= edit.start)
+      .saturating_sub(1);
+    let restart = edited.saturating_sub(1);
+    let replaced_end = edit.start + replacement.len();
+    let mut reused = starts.len();
+    let (nodes, resync) = parser::parse_from(
+      source,
+      starts.get(restart).copied().unwrap_or_else(|| Position::new(1, 1, 0)),
+      |next| {
+        // A line after the edit lines up with an old node if it starts where
+        // that node started, once shifted by the length of the edit.
+        let Some(offset) =
+          (next.offset() + edit.end).checked_sub(replaced_end).filter(
+            |&offset| next.offset() >= replaced_end && offset >= edit.end,
+          )
+        else {
+          return false;
+        };
+
+        starts
+          .binary_search_by_key(&offset, Position::offset)
+          .map(|index| reused = index)
+          .is_ok()
+      },
+    );
+    let mut inner = self.inner()[..restart].to_vec();
+    let mut spans = self.spans()[..restart].to_vec();
+
+    for (node, span) in nodes {
+      inner.push(Node::from(node));
+      spans.push(span);
+    }
+
+    let mut changed = restart..inner.len();
+
+    if let Some(next) = resync {
+      let from = starts[reused];
+
+      inner.extend_from_slice(&self.inner()[reused..]);
+      spans.extend(
+        self.spans()[reused..].iter().map(|span| span.shift(from, next)),
+      );
+    }
+
+    // Nodes at either end of the re-parsed range may have come out as they
+    // were, in which case they did not change.
+    while changed.start < changed.end
+      && self.inner().get(changed.start) == inner.get(changed.start)
+      && self.spans().get(changed.start) == spans.get(changed.start)
+    {
+      changed.start += 1;
+    }
+
+    if let Some(next) = resync {
+      let mut old = reused;
+
+      while changed.start < changed.end
+        && old > changed.start
+        && starts[old - 1].offset() >= edit.end
+        && self.inner()[old - 1] == inner[changed.end - 1]
+        && self.spans()[old - 1].shift(starts[reused], next)
+          == spans[changed.end - 1]
+      {
+        changed.end -= 1;
+        old -= 1;
+      }
+    }
+
+    (Self::from_parts(inner, spans), changed)
+  }
+}
@@ -0,0 +1,235 @@
+// This file is part of Germ <https://github.com/gemrest/germ>.
+// Copyright (C) 2022-2022 Fuwn <contact@fuwn.me>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+//
+// Copyright (C) 2022-2022 Fuwn <contact@fuwn.me>
+// SPDX-License-Identifier: GPL-3.0-only
+
+use {
+  super::{Ast, Node, Span},
+  std::fmt,
+  url::{ParseError, Url},
+};
+
+/// Where a link line points to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LinkTarget {
+  /// An absolute URL
+  Url(Url),
+  /// A reference relative to the URL of the document the link is in
+  Relative(RelativeReference),
+}
+
+/// A relative reference, by which part of its base URL it replaces
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RelativeReference {
+  /// A reference which replaces everything but the scheme, e.g.,
+  /// `//gem.rest/`
+  NetworkPath(String),
+  /// A reference which replaces the path, e.g., `/foo`
+  AbsolutePath(String),
+  /// A reference which is resolved against the path, e.g., `../bar`
+  RelativePath(String),
+  /// A reference which only replaces the query, e.g., `?query`
+  Query(String),
+  /// A reference which only replaces the fragment, e.g., `#heading`
+  Fragment(String),
+}
+
+impl RelativeReference {
+  fn new(reference: &str) -> Self {
+    let reference = reference.to_string();
+
+    if reference.starts_with("//") {
+      Self::NetworkPath(reference)
+    } else if reference.starts_with('/') {
+      Self::AbsolutePath(reference)
+    } else if reference.starts_with('?') {
+      Self::Query(reference)
+    } else if reference.starts_with('#') {
+      Self::Fragment(reference)
+    } else {
+      Self::RelativePath(reference)
+    }
+  }
+
+  /// The reference as it was written
+  #[must_use]
+  pub fn as_str(&self) -> &str {
+    match self {
+      Self::NetworkPath(reference)
+      | Self::AbsolutePath(reference)
+      | Self::RelativePath(reference)
+      | Self::Query(reference)
+      | Self::Fragment(reference) => reference,
+    }
+  }
+
+  /// Resolve the reference against the URL of its document
+  ///
+  /// # Example
+  ///
+  /// ```rust
+  /// use germ::ast::{LinkTarget, Node};
+  ///
+  /// let link = Node::Link { to: "../b".to_string(), text: None };
+  /// let base = url::Url::parse("gemini://gem.rest/a/c.gmi").unwrap();
+  ///
+  /// if let Some(Ok(LinkTarget::Relative(reference))) = link.link_target() {
+  ///   assert_eq!(
+  ///     reference.resolve(&base).unwrap().as_str(),
+  ///     "gemini://gem.rest/b"
+  ///   );
+  /// }
+  /// ```
+  ///
+  /// # Errors
+  ///
+  /// - Errors if the resolved URL is invalid, or if `base` can't be resolved
+  ///   against, e.g., `mailto:` URLs
+  pub fn resolve(&self, base: &Url) -> Result<Url, ParseError> {
+    base.join(self.as_str())
+  }
+}
+
+impl Node {
+  /// Parse where a link line points to, or `None` if the `Node` is not a link
+  /// line
+  ///
+  /// # Example
+  ///
+  /// ```rust
+  /// use germ::ast::{LinkTarget, Node, RelativeReference};
+  ///
+  /// let link = Node::Link { to: "/about".to_string(), text: None };
+  ///
+  /// assert_eq!(
+  ///   link.link_target(),
+  ///   Some(Ok(LinkTarget::Relative(RelativeReference::AbsolutePath(
+  ///     "/about".to_string()
+  ///   ))))
+  /// );
+  /// ```
+  ///
+  /// # Errors
+  ///
+  /// - Errors if the location of the link line is neither a valid URL nor a
+  ///   valid relative reference
+  #[must_use]
+  pub fn link_target(&self) -> Option<Result<LinkTarget, ParseError>> {
+    let Self::Link { to, .. } = self else {
+      return None;
+    };
+
+    Some(match Url::parse(to) {
+      Ok(url) => Ok(LinkTarget::Url(url)),
+      Err(ParseError::RelativeUrlWithoutBase) => {
+        // A relative reference is only invalid if it can't be resolved
+        // against any base, so resolving it against a placeholder is enough
+        // to validate it.
+        let reference = RelativeReference::new(to);
+
+        Url::parse("gemini://germ.invalid/")
+          .and_then(|base| reference.resolve(&base))
+          .map(|_| LinkTarget::Relative(reference))
+      }
+      Err(error) => Err(error),
+    })
+  }
+}
+
+/// A link line whose location could not be resolved by
+/// [`Ast::resolve_links`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidLink {
+  index: usize,
+  to:    String,
+  span:  Option<Span>,
+  error: ParseError,
+}
+
+impl InvalidLink {
+  /// The index of the link line within its [`Ast`]
+  #[must_use]
+  pub const fn index(&self) -> usize { self.index }
+
+  /// The location of the link line, as it was written
+  #[must_use]
+  pub fn to(&self) -> &str { &self.to }
+
+  /// Where in the source the location of the link line is, if known
+  #[must_use]
+  pub const fn span(&self) -> Option<Span> { self.span }
+
+  /// Why the location could not be resolved
+  #[must_use]
+  pub const fn error(&self) -> ParseError { self.error }
+}
+
+impl fmt::Display for InvalidLink {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    if let Some(span) = self.span {
+      write!(f, "{}:{}: ", span.start().line(), span.start().column())?;
+    }
+
+    write!(f, "invalid link location `{}`: {}", self.to, self.error)
+  }
+}
+
+impl std::error::Error for InvalidLink {}
+
+impl Ast {
+  /// Resolve the location of every link line of `Ast` against the URL of the
+  /// document, so that each one becomes an absolute URL
+  ///
+  /// The link lines which can't be resolved are left as they are, and are
+  /// reported back with their spans. The resolved locations no longer match
+  /// the source, so `Ast` forgets its spans.
+  ///
+  /// # Example
+  ///
+  /// ```rust
+  /// let base = url::Url::parse("gemini://gem.rest/a/c.gmi").unwrap();
+  /// let mut ast = germ::ast::Ast::from_string("=> ../b B\n=> ?q\n=> http://[::1");
+  /// let invalid = ast.resolve_links(&base);
+  ///
+  /// assert_eq!(
+  ///   ast.to_gemtext(),
+  ///   "=> gemini://gem.rest/b B\n=> gemini://gem.rest/a/c.gmi?q\n=> \
+  ///    http://[::1"
+  /// );
+  /// assert_eq!(invalid[0].index(), 2);
+  /// ```
+  pub fn resolve_links(&mut self, base: &Url) -> Vec<InvalidLink> {
+    let spans =
+      self.spans().iter().map(super::NodeSpan::to).collect::<Vec<_>>();
+    let mut invalid = vec![];
+
+    for (index, node) in self.iter_mut().enumerate() {
+      if let Node::Link { to, .. } = node {
+        match base.join(to) {
+          Ok(url) => *to = url.to_string(),
+          Err(error) => invalid.push(InvalidLink {
+            index,
+            to: to.clone(),
+            span: spans.get(index).copied().flatten(),
+            error,
+          }),
+        }
+      }
+    }
+
+    invalid
+  }
+}
@@ -0,0 +1,232 @@
+// This file is part of Germ <https://github.com/gemrest/germ>.
+// Copyright (C) 2022-2022 Fuwn <contact@fuwn.me>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+//
+// Copyright (C) 2022-2022 Fuwn <contact@fuwn.me>
+// SPDX-License-Identifier: GPL-3.0-only
+
+use {
+  super::{Ast, Node, Slugger},
+  crate::escape::escape,
+};
+
+/// A heading and everything below it, up to the next heading of the same or a
+/// higher level
+///
+/// The nodes before the first heading of a document make up a section of
+/// their own, which has no heading and a level of zero.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Section<'a> {
+  heading:  Option<&'a str>,
+  level:    usize,
+  body:     &'a [Node],
+  children: Vec<Section<'a>>,
+}
+
+impl<'a> Section<'a> {
+  /// The text of the heading of the `Section`
+  #[must_use]
+  pub const fn heading(&self) -> Option<&'a str> { self.heading }
+
+  /// The level of the heading of the `Section`
+  #[must_use]
+  pub const fn level(&self) -> usize { self.level }
+
+  /// The nodes between the heading of the `Section` and the next heading
+  #[must_use]
+  pub const fn body(&self) -> &'a [Node] { self.body }
+
+  /// The sections under deeper headings within the `Section`
+  #[must_use]
+  pub fn children(&self) -> &[Self] { &self.children }
+}
+
+/// A table of contents of a document, built from its headings
+///
+/// Each heading is linked to by its unique [`slug`](fn@super::slug), which is
+/// also the `id` the heading is given when converted into HTML.
+///
+/// # Example
+///
+/// ```rust
+/// let ast = germ::ast::Ast::from_string("# Hi\n## There\nText\n# Bye");
+///
+/// assert_eq!(
+///   ast.outline().to_gemtext(),
+///   "=> #hi Hi\n=> #there There\n=> #bye Bye"
+/// );
+/// assert_eq!(
+///   ast.outline().to_html(),
+///   "<ul><li><a href=\"#hi\">Hi</a><ul><li><a \
+///    href=\"#there\">There</a></li></ul></li><li><a \
+///    href=\"#bye\">Bye</a></li></ul>"
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Outline<'a> {
+  sections: Vec<Section<'a>>,
+}
+
+impl<'a> Outline<'a> {
+  /// The sections of the `Outline`, which all have headings
+  #[must_use]
+  pub fn sections(&self) -> &[Section<'a>] { &self.sections }
+
+  /// Emit the `Outline` as Gemtext link lines, one per heading, in document
+  /// order
+  #[must_use]
+  pub fn to_gemtext(&self) -> String {
+    fn walk(
+      sections: &[Section<'_>],
+      slugger: &mut Slugger,
+      lines: &mut Vec<String>,
+    ) {
+      for section in sections {
+        let heading = section.heading.unwrap_or_default();
+
+        lines.push(format!("=> #{} {heading}", slugger.slug(heading)));
+        walk(&section.children, slugger, lines);
+      }
+    }
+
+    let mut lines = vec![];
+
+    walk(&self.sections, &mut Slugger::new(), &mut lines);
+
+    lines.join("\n")
+  }
+
+  /// Emit the `Outline` as nested HTML lists of links
+  #[must_use]
+  pub fn to_html(&self) -> String {
+    fn walk(
+      sections: &[Section<'_>],
+      slugger: &mut Slugger,
+      html: &mut String,
+    ) {
+      if sections.is_empty() {
+        return;
+      }
+
+      html.push_str("<ul>");
+
+      for section in sections {
+        let heading = section.heading.unwrap_or_default();
+
+        html.push_str(&format!(
+          "<li><a href=\"#{}\">{}</a>",
+          slugger.slug(heading),
+          escape(heading)
+        ));
+        walk(&section.children, slugger, html);
+        html.push_str("</li>");
+      }
+
+      html.push_str("</ul>");
+    }
+
+    let mut html = String::new();
+
+    walk(&self.sections, &mut Slugger::new(), &mut html);
+
+    html
+  }
+}
+
+impl Ast {
+  /// Build a tree of the sections of `Ast` from its headings
+  ///
+  /// # Example
+  ///
+  /// ```rust
+  /// let ast = germ::ast::Ast::from_string("Intro\n# A\n## B\nText\n# C");
+  /// let sections = ast.sections();
+  ///
+  /// assert_eq!(sections[0].heading(), None);
+  /// assert_eq!(sections[1].heading(), Some("A"));
+  /// assert_eq!(sections[1].children()[0].body().len(), 1);
+  /// assert_eq!(sections[2].heading(), Some("C"));
+  /// ```
+  #[must_use]
+  pub fn sections(&self) -> Vec<Section<'_>> {
+    let nodes = self.inner();
+    let headings = nodes
+      .iter()
+      .enumerate()
+      .filter_map(|(index, node)| match node {
+        Node::Heading { level, text } => Some((index, *level, text.as_str())),
+        _ => None,
+      })
+      .collect::<Vec<_>>();
+    let first = headings.first().map_or(nodes.len(), |(index, ..)| *index);
+    let mut sections = vec![];
+    let mut open: Vec<Section<'_>> = vec![];
+
+    if first > 0 {
+      sections.push(Section {
+        heading:  None,
+        level:    0,
+        body:     &nodes[..first],
+        children: vec![],
+      });
+    }
+
+    for (position, (index, level, text)) in headings.iter().enumerate() {
+      let end = headings.get(position + 1).map_or(nodes.len(), |next| next.0);
+
+      // Headings of the same or a higher level close every section which is
+      // still open at their level or deeper.
+      while open.last().is_some_and(|section| section.level >= *level) {
+        close(&mut open, &mut sections);
+      }
+
+      open.push(Section {
+        heading:  Some(text),
+        level:    *level,
+        body:     &nodes[index + 1..end],
+        children: vec![],
+      });
+    }
+
+    while !open.is_empty() {
+      close(&mut open, &mut sections);
+    }
+
+    sections
+  }
+
+  /// Build a table of contents of `Ast` from its headings
+  ///
+  /// See [`Outline`] for an example.
+  #[must_use]
+  pub fn outline(&self) -> Outline<'_> {
+    Outline {
+      sections: self
+        .sections()
+        .into_iter()
+        .filter(|section| section.heading.is_some())
+        .collect(),
+    }
+  }
+}
+
+/// Close the innermost open section, attaching it to its parent
+fn close<'a>(open: &mut Vec<Section<'a>>, sections: &mut Vec<Section<'a>>) {
+  if let Some(section) = open.pop() {
+    open
+      .last_mut()
+      .map_or(sections, |parent| &mut parent.children)
+      .push(section);
+  }
+}
@@ -0,0 +1,105 @@
+// This file is part of Germ <https://github.com/gemrest/germ>.
+// Copyright (C) 2022-2022 Fuwn <contact@fuwn.me>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+//
+// Copyright (C) 2022-2022 Fuwn <contact@fuwn.me>
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::collections::HashMap;
+
+/// Turn the text of a heading into the anchor it is linked to by
+///
+/// The text is lowercased, its whitespace is replaced with hyphens, and
+/// everything but letters, numbers, hyphens, and underscores is dropped, in
+/// any script, much like the anchors which Markdown renderers like GitHub's
+/// give headings. These are the anchors of headings in the HTML and Markdown
+/// which Gemtext is converted into.
+///
+/// Use a [`Slugger`] to keep the anchors of a whole document unique.
+///
+/// # Example
+///
+/// ```rust
+/// use germ::ast::slug;
+///
+/// assert_eq!(slug("Getting Started!"), "getting-started");
+/// assert_eq!(slug("Ünïcödé — Überschrift"), "ünïcödé--überschrift");
+/// assert_eq!(slug("snake_case & kebab-case"), "snake_case--kebab-case");
+/// ```
+#[must_use]
+pub fn slug(text: &str) -> String {
+  text
+    .trim()
+    .chars()
+    .filter_map(|character| {
+      if character.is_whitespace() {
+        Some('-')
+      } else if character.is_alphanumeric() || matches!(character, '-' | '_') {
+        Some(character)
+      } else {
+        None
+      }
+    })
+    .flat_map(char::to_lowercase)
+    .collect()
+}
+
+/// Slugs headings into anchors which are unique within a document
+///
+/// The first heading of a text gets its plain [`slug`], and each repeat gets
+/// a numbered suffix, e.g., `faq`, `faq-1`, `faq-2`. Headings without any
+/// letters or numbers, which slug to nothing, are slugged as `section`.
+///
+/// # Example
+///
+/// ```rust
+/// use germ::ast::Slugger;
+///
+/// let mut slugger = Slugger::new();
+///
+/// assert_eq!(slugger.slug("FAQ"), "faq");
+/// assert_eq!(slugger.slug("FAQ"), "faq-1");
+/// assert_eq!(slugger.slug("FAQ 1"), "faq-1-1");
+/// assert_eq!(slugger.slug("!!!"), "section");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Slugger {
+  occurrences: HashMap<String, usize>,
+}
+
+impl Slugger {
+  /// Create a new `Slugger` which has not given out any anchors
+  #[must_use]
+  pub fn new() -> Self { Self::default() }
+
+  /// Slug the text of a heading into an anchor which has not been given out
+  /// yet
+  pub fn slug(&mut self, text: &str) -> String {
+    let original = Some(slug(text))
+      .filter(|slug| !slug.is_empty())
+      .unwrap_or_else(|| "section".to_string());
+    let mut slug = original.clone();
+
+    while self.occurrences.contains_key(&slug) {
+      let occurrences = self.occurrences.entry(original.clone()).or_default();
+
+      *occurrences += 1;
+      slug = format!("{original}-{occurrences}");
+    }
+
+    self.occurrences.insert(slug.clone(), 0);
+
+    slug
+  }
+}
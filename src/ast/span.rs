@@ -0,0 +1,190 @@
+// This file is part of Germ <https://github.com/gemrest/germ>.
+// Copyright (C) 2022-2022 Fuwn <contact@fuwn.me>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+//
+// Copyright (C) 2022-2022 Fuwn <contact@fuwn.me>
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::ops::Range;
+
+/// A location within a Gemtext source
+///
+/// Lines and columns are one-based, and columns are counted in characters.
+/// Offsets are zero-based and counted in bytes.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Position {
+  line:   usize,
+  column: usize,
+  offset: usize,
+}
+
+impl Position {
+  /// Create a new `Position`
+  ///
+  /// # Example
+  ///
+  /// ```rust
+  /// let position = germ::ast::Position::new(1, 1, 0);
+  ///
+  /// assert_eq!(position.line(), 1);
+  /// ```
+  #[must_use]
+  pub const fn new(line: usize, column: usize, offset: usize) -> Self {
+    Self { line, column, offset }
+  }
+
+  /// The one-based line of the `Position`
+  #[must_use]
+  pub const fn line(&self) -> usize { self.line }
+
+  /// The one-based column of the `Position`, counted in characters
+  #[must_use]
+  pub const fn column(&self) -> usize { self.column }
+
+  /// The zero-based byte offset of the `Position`
+  #[must_use]
+  pub const fn offset(&self) -> usize { self.offset }
+
+  /// Move a `Position` at or after the line start `from` along with it, so
+  /// that `from` lands on the line start `to`
+  pub(crate) const fn shift(self, from: Self, to: Self) -> Self {
+    Self {
+      line:   self.line + to.line - from.line,
+      column: self.column,
+      offset: self.offset + to.offset - from.offset,
+    }
+  }
+}
+
+/// A region of a Gemtext source, from `start` up to, but not including, `end`
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Span {
+  start: Position,
+  end:   Position,
+}
+
+impl Span {
+  /// Create a new `Span`
+  #[must_use]
+  pub const fn new(start: Position, end: Position) -> Self {
+    Self { start, end }
+  }
+
+  /// The first position of the `Span`
+  #[must_use]
+  pub const fn start(&self) -> Position { self.start }
+
+  /// The position directly after the `Span`
+  #[must_use]
+  pub const fn end(&self) -> Position { self.end }
+
+  /// The byte range the `Span` covers, ready to index the source with
+  ///
+  /// # Example
+  ///
+  /// ```rust
+  /// let source = "=> / Home";
+  /// let ast = germ::ast::Ast::from_string(source);
+  /// let to = ast.spans()[0].to().unwrap();
+  ///
+  /// assert_eq!(&source[to.range()], "/");
+  /// ```
+  #[must_use]
+  pub const fn range(&self) -> Range<usize> {
+    self.start.offset..self.end.offset
+  }
+
+  pub(crate) const fn shift(self, from: Position, to: Position) -> Self {
+    Self { start: self.start.shift(from, to), end: self.end.shift(from, to) }
+  }
+}
+
+/// The source locations of a [`Node`](super::Node) and its sub-parts
+///
+/// Sub-parts which a node does not have are `None` (or empty, for `items`).
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+#[allow(clippy::module_name_repetitions)]
+pub struct NodeSpan {
+  span:     Span,
+  to:       Option<Span>,
+  text:     Option<Span>,
+  alt_text: Option<Span>,
+  items:    Vec<Span>,
+}
+
+impl NodeSpan {
+  pub(crate) const fn new(span: Span) -> Self {
+    Self { span, to: None, text: None, alt_text: None, items: Vec::new() }
+  }
+
+  pub(crate) const fn with_to(mut self, to: Span) -> Self {
+    self.to = Some(to);
+
+    self
+  }
+
+  pub(crate) const fn with_text(mut self, text: Span) -> Self {
+    self.text = Some(text);
+
+    self
+  }
+
+  pub(crate) const fn with_alt_text(mut self, alt_text: Span) -> Self {
+    self.alt_text = Some(alt_text);
+
+    self
+  }
+
+  pub(crate) fn with_items(mut self, items: Vec<Span>) -> Self {
+    self.items = items;
+
+    self
+  }
+
+  /// Move every span of a node which starts at or after the line start `from`
+  /// along with it, so that `from` lands on the line start `to`
+  pub(crate) fn shift(&self, from: Position, to: Position) -> Self {
+    Self {
+      span:     self.span.shift(from, to),
+      to:       self.to.map(|span| span.shift(from, to)),
+      text:     self.text.map(|span| span.shift(from, to)),
+      alt_text: self.alt_text.map(|span| span.shift(from, to)),
+      items:    self.items.iter().map(|span| span.shift(from, to)).collect(),
+    }
+  }
+
+  /// The whole node, including its line identifiers
+  ///
+  /// Multi-line nodes, lists and preformatted blocks, span from the start of
+  /// their first line to the end of their last line.
+  #[must_use]
+  pub const fn span(&self) -> Span { self.span }
+
+  /// The location a link line is pointing to
+  #[must_use]
+  pub const fn to(&self) -> Option<Span> { self.to }
+
+  /// The text of a text, link, heading or blockquote line, or the content of
+  /// a preformatted block
+  #[must_use]
+  pub const fn text(&self) -> Option<Span> { self.text }
+
+  /// The alt-text of a preformatted block
+  #[must_use]
+  pub const fn alt_text(&self) -> Option<Span> { self.alt_text }
+
+  /// The text of each item of a list
+  #[must_use]
+  pub fn items(&self) -> &[Span] { &self.items }
+}
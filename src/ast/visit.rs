@@ -0,0 +1,82 @@
+// This file is part of Germ <https://github.com/gemrest/germ>.
+// Copyright (C) 2022-2022 Fuwn <contact@fuwn.me>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+//
+// Copyright (C) 2022-2022 Fuwn <contact@fuwn.me>
+// SPDX-License-Identifier: GPL-3.0-only
+
+use {super::borrowed::Node, std::borrow::Cow};
+
+/// Walk the nodes of an AST tree, one default method per node type
+///
+/// Every method does nothing by default, so a visitor only has to implement
+/// the methods of the node types it is interested in. Both owned and
+/// [`borrowed`](super::borrowed) AST trees can be visited.
+///
+/// # Example
+///
+/// ```rust
+/// use germ::ast::{Ast, Visit};
+///
+/// #[derive(Default)]
+/// struct Links(Vec<String>);
+///
+/// impl Visit for Links {
+///   fn visit_link(&mut self, to: &str, _text: Option<&str>) {
+///     self.0.push(to.to_string());
+///   }
+/// }
+///
+/// let mut links = Links::default();
+///
+/// Ast::from_string("# Hi\n=> /a A\n=> /b").visit(&mut links);
+///
+/// assert_eq!(links.0, vec!["/a", "/b"]);
+/// ```
+pub trait Visit {
+  /// Visit any node, dispatching to the method of its node type
+  fn visit_node(&mut self, node: &Node<'_>) {
+    match node {
+      Node::Text(text) => self.visit_text(text),
+      Node::Link { to, text } => self.visit_link(to, text.as_deref()),
+      Node::Heading { level, text } => self.visit_heading(*level, text),
+      Node::List(items) => self.visit_list(items),
+      Node::Blockquote(text) => self.visit_blockquote(text),
+      Node::PreformattedText { alt_text, text } =>
+        self.visit_preformatted_text(alt_text.as_deref(), text),
+      Node::Whitespace => self.visit_whitespace(),
+    }
+  }
+
+  /// Visit a text line
+  fn visit_text(&mut self, _text: &str) {}
+
+  /// Visit a link line
+  fn visit_link(&mut self, _to: &str, _text: Option<&str>) {}
+
+  /// Visit a heading line
+  fn visit_heading(&mut self, _level: usize, _text: &str) {}
+
+  /// Visit a collection of sequential list item lines
+  fn visit_list(&mut self, _items: &[Cow<'_, str>]) {}
+
+  /// Visit a blockquote line
+  fn visit_blockquote(&mut self, _text: &str) {}
+
+  /// Visit a preformatted block
+  fn visit_preformatted_text(&mut self, _alt_text: Option<&str>, _text: &str) {}
+
+  /// Visit a whitespace line
+  fn visit_whitespace(&mut self) {}
+}
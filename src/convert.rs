@@ -18,24 +18,177 @@
 
 //! Convert Gemtext into many types of markup.
 
-use crate::ast::Ast;
+use {
+  crate::ast::{borrowed, Node, Visit},
+  std::{fmt, io},
+};
 
+mod from_html;
+mod from_markdown;
 mod html;
+mod literal;
 mod markdown;
+mod plain_text;
+mod render;
+mod sink;
 
 #[cfg(feature = "macros")] mod macros;
 
+#[cfg(feature = "highlight")] pub mod highlight;
+
+pub use {
+  from_html::from_html, from_markdown::from_markdown, html::HtmlOptions,
+  render::Renderer,
+};
+
 /// Different targets to convert Gemtext to
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum Target {
   /// Convert Gemtext to HTML
+  ///
+  /// Text and link locations are escaped, and links with schemes which run
+  /// code, like `javascript:` and `data:`, lose their locations.
+  ///
+  /// Each heading is given the unique [`slug`](fn@crate::ast::slug) of its text
+  /// as its `id`.
+  ///
+  /// # Example
+  ///
+  /// ```rust
+  /// use germ::convert::{from_string, Target};
+  ///
+  /// assert_eq!(
+  ///   from_string("# Hi\n<b>\n=> javascript:alert(1) Hi", &Target::HTML),
+  ///   "<h1 id=\"hi\">Hi</h1><p>&lt;b&gt;</p><a>Hi</a><br>"
+  /// );
+  /// ```
   HTML,
+  /// Convert Gemtext to HTML without escaping it or neutralising its links,
+  /// for Gemtext which is trusted to contain markup
+  ///
+  /// # Example
+  ///
+  /// ```rust
+  /// use germ::convert::{from_string, Target};
+  ///
+  /// assert_eq!(
+  ///   from_string("<b>Hi</b>", &Target::TrustedHTML),
+  ///   "<p><b>Hi</b></p>"
+  /// );
+  /// ```
+  TrustedHTML,
   /// Convert Gemtext to Markdown
+  ///
+  /// Each heading is led by an explicit `<a id="...">` anchor, which is the
+  /// same as its `id` in [`Target::HTML`], so in-page links work the same
+  /// way in both.
+  ///
+  /// # Example
+  ///
+  /// ```rust
+  /// use germ::convert::{from_string, Target};
+  ///
+  /// assert_eq!(
+  ///   from_string("# FAQ\n## Why?", &Target::Markdown),
+  ///   "# <a id=\"faq\"></a>FAQ\n## <a id=\"why\"></a>Why?\n"
+  /// );
+  /// ```
   Markdown,
+  /// Convert Gemtext to plain text, without any Gemtext syntax
+  ///
+  /// # Example
+  ///
+  /// ```rust
+  /// use germ::convert::{from_string, LinkStyle, Target};
+  ///
+  /// let gemtext = "# Hi\n=> /about About\n* Item\n> Quote";
+  ///
+  /// assert_eq!(
+  ///   from_string(gemtext, &Target::PlainText(LinkStyle::Inline)),
+  ///   "Hi\nAbout </about>\n• Item\n    Quote\n"
+  /// );
+  /// assert_eq!(
+  ///   from_string(gemtext, &Target::PlainText(LinkStyle::References)),
+  ///   "Hi\nAbout [1]\n• Item\n    Quote\n\n[1] /about\n"
+  /// );
+  /// ```
+  PlainText(LinkStyle),
+}
+
+/// How links are written out in plain text
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum LinkStyle {
+  /// Write each link as its text followed by its location, `text <url>`
+  #[default]
+  Inline,
+  /// Write each link as its text followed by a numbered reference, `text
+  /// [1]`, and list the locations of the references after the text
+  References,
+}
+
+/// Where the link lines of anchors go when converting HTML into Gemtext
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum LinkPlacement {
+  /// Split the block an anchor is in, placing its link line where it was
+  ///
+  /// The anchors of list items follow the whole list instead.
+  Inline,
+  /// Number each anchor in its text, e.g., `text [1]`, and list the link lines
+  /// of every anchor at the end of the document
+  Footnotes,
+  /// Place the link lines of the anchors of a block after the block
+  #[default]
+  Paragraph,
+}
+
+/// How runs of consecutive link lines are wrapped when converting Gemtext into
+/// HTML
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum LinkRuns {
+  /// Follow each link with a `<br>`
+  #[default]
+  Breaks,
+  /// Wrap each run of links in a `<ul>`, one `<li>` per link
+  List,
+}
+
+/// The types of nodes, for styling the HTML elements they are converted into
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum NodeKind {
+  /// A text line
+  Text,
+  /// A link line
+  Link,
+  /// A heading line
+  Heading,
+  /// A collection of sequential list item lines
+  List,
+  /// A blockquote line
+  Blockquote,
+  /// A preformatted block
+  PreformattedText,
+  /// A whitespace line
+  Whitespace,
+}
+
+impl NodeKind {
+  const fn index(self) -> usize {
+    match self {
+      Self::Text => 0,
+      Self::Link => 1,
+      Self::Heading => 2,
+      Self::List => 3,
+      Self::Blockquote => 4,
+      Self::PreformattedText => 5,
+      Self::Whitespace => 6,
+    }
+  }
 }
 
 /// Convert AST'd Gemtext into an alternative markup format.
 ///
+/// Both owned and [`borrowed`] AST trees can be converted.
+///
 /// # Example
 ///
 /// ```rust
@@ -45,12 +198,200 @@ pub enum Target {
 ///   &germ::ast::Ast::from_string(r#"=> gemini://gem.rest/ GemRest"#),
 ///   &convert::Target::HTML,
 /// );
+/// let _ = convert::from_ast(
+///   germ::ast::borrowed::Ast::from_value(r#"=> gemini://gem.rest/ GemRest"#),
+///   &convert::Target::Markdown,
+/// );
 /// ```
 #[must_use]
-pub fn from_ast(source: &Ast, target: &Target) -> String {
+pub fn from_ast<'a>(
+  source: impl Into<borrowed::Ast<'a>>,
+  target: &Target,
+) -> String {
+  let mut output = String::new();
+
+  // Writing into a `String` never fails.
+  let _ = write_ast_fmt(source, target, &mut output);
+
+  output
+}
+
+/// Convert AST'd Gemtext into an alternative markup format, writing it out
+/// node by node rather than building it up in a `String`
+///
+/// Both owned and [`borrowed`] AST trees can be converted. The output is
+/// buffered, so `writer` need not be.
+///
+/// # Example
+///
+/// ```rust
+/// use germ::{ast::Ast, convert};
+///
+/// let mut html = vec![];
+///
+/// convert::write_ast(
+///   &Ast::from_string("# Hi\n=> / Home"),
+///   &convert::Target::HTML,
+///   &mut html,
+/// )
+/// .unwrap();
+///
+/// assert_eq!(html, b"<h1 id=\"hi\">Hi</h1><a href=\"/\">Home</a><br>");
+/// ```
+///
+/// # Errors
+///
+/// - Errors if writing to `writer` fails
+pub fn write_ast<'a>(
+  source: impl Into<borrowed::Ast<'a>>,
+  target: &Target,
+  writer: &mut impl io::Write,
+) -> io::Result<()> {
+  let mut writer = sink::IoWriter::new(writer);
+
+  write_ast_fmt(source, target, &mut writer).map_err(|_| writer.error())?;
+  writer.flush()
+}
+
+/// Convert AST'd Gemtext into an alternative markup format, writing it out
+/// node by node into a [`fmt::Write`], like a `String` or a [`fmt::Formatter`]
+///
+/// # Example
+///
+/// ```rust
+/// use germ::{ast::Ast, convert};
+///
+/// let mut markdown = String::from("<!-- Converted -->\n");
+///
+/// convert::write_ast_fmt(
+///   &Ast::from_string("=> / Home"),
+///   &convert::Target::Markdown,
+///   &mut markdown,
+/// )
+/// .unwrap();
+///
+/// assert_eq!(markdown, "<!-- Converted -->\n[Home](/)\n");
+/// ```
+///
+/// # Errors
+///
+/// - Errors if writing to `writer` fails
+pub fn write_ast_fmt<'a>(
+  source: impl Into<borrowed::Ast<'a>>,
+  target: &Target,
+  writer: &mut impl fmt::Write,
+) -> fmt::Result {
+  let source = source.into();
+
+  render_target(*target, writer, |renderer| render_with(&source, renderer))
+}
+
+/// Convert Gemtext into an alternative markup format as it is parsed, node by
+/// node
+///
+/// Paired with a [`Parser`](crate::ast::Parser), a document is converted
+/// without ever being held in memory as a whole. The output is buffered, so
+/// `writer` need not be.
+///
+/// # Example
+///
+/// ```rust
+/// use germ::{ast::Parser, convert};
+///
+/// let mut html = vec![];
+///
+/// convert::write_nodes(
+///   Parser::new("text\n* item".as_bytes()),
+///   &convert::Target::HTML,
+///   &mut html,
+/// )
+/// .unwrap();
+///
+/// assert_eq!(html, b"<p>text</p><ul><li>item</li></ul>");
+/// ```
+///
+/// # Errors
+///
+/// - Errors if reading a node fails, e.g., on invalid UTF-8, or if writing to
+///   `writer` fails
+pub fn write_nodes(
+  nodes: impl IntoIterator<Item = io::Result<Node>>,
+  target: &Target,
+  writer: &mut impl io::Write,
+) -> io::Result<()> {
+  let mut writer = sink::IoWriter::new(writer);
+  let mut read_error = None;
+  let result = render_target(*target, &mut writer, |renderer| {
+    renderer.start_document();
+
+    for node in nodes {
+      match node {
+        Ok(node) => render::Render(&mut *renderer)
+          .visit_node(&borrowed::Node::from(&node)),
+        Err(error) => {
+          read_error = Some(error);
+
+          return;
+        }
+      }
+    }
+
+    renderer.end_document();
+  });
+
+  if let Some(error) = read_error {
+    return Err(error);
+  }
+
+  result.map_err(|_| writer.error())?;
+  writer.flush()
+}
+
+/// Render AST'd Gemtext with a custom [`Renderer`]
+///
+/// Both owned and [`borrowed`] AST trees can be rendered. See [`Renderer`] for
+/// an example.
+pub fn render_with<'a>(
+  source: impl Into<borrowed::Ast<'a>>,
+  renderer: &mut (impl Renderer + ?Sized),
+) {
+  renderer.start_document();
+  source.into().visit(&mut render::Render(renderer));
+  renderer.end_document();
+}
+
+/// Hand the built-in renderer of `target` to `render`, writing its output into
+/// `writer`
+fn render_target(
+  target: Target,
+  writer: impl fmt::Write,
+  render: impl FnOnce(&mut dyn Renderer),
+) -> fmt::Result {
+  // Since we have an AST tree of the Gemtext, it is very easy to convert from
+  // this AST tree to an alternative markup format.
   match target {
-    Target::Markdown => markdown::convert(source.inner()),
-    Target::HTML => html::convert(source.inner()),
+    Target::Markdown => {
+      let mut markdown = markdown::Markdown::new(writer);
+
+      render(&mut markdown);
+
+      markdown.finish()
+    }
+    Target::HTML | Target::TrustedHTML => {
+      let options = HtmlOptions::new().trusted(target == Target::TrustedHTML);
+      let mut html = html::Html::new(writer, &options, &|_, _| None);
+
+      render(&mut html);
+
+      html.finish()
+    }
+    Target::PlainText(links) => {
+      let mut plain_text = plain_text::PlainText::new(writer, links);
+
+      render(&mut plain_text);
+
+      plain_text.finish()
+    }
   }
 }
 
@@ -71,5 +412,5 @@ pub fn from_string(
   source: &(impl ToString + ?Sized),
   target: &Target,
 ) -> String {
-  from_ast(&Ast::from_owned(&source.to_string()), target)
+  from_ast(borrowed::Ast::from_value(&source.to_string()), target)
 }
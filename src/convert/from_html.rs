@@ -0,0 +1,514 @@
+// This file is part of Germ <https://github.com/gemrest/germ>.
+// Copyright (C) 2022-2022 Fuwn <contact@fuwn.me>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+//
+// Copyright (C) 2022-2022 Fuwn <contact@fuwn.me>
+// SPDX-License-Identifier: GPL-3.0-only
+
+use {
+  super::{literal::literal, LinkPlacement},
+  crate::ast::{Ast, Node},
+};
+
+/// Elements which are dropped along with everything in them
+const DROPPED: [&str; 6] =
+  ["head", "nav", "noscript", "script", "style", "template"];
+
+/// Elements which begin a new block, ending the text before them
+const BLOCKS: [&str; 27] = [
+  "address",
+  "article",
+  "aside",
+  "blockquote",
+  "body",
+  "dd",
+  "details",
+  "div",
+  "dl",
+  "dt",
+  "fieldset",
+  "figcaption",
+  "figure",
+  "footer",
+  "form",
+  "header",
+  "hr",
+  "html",
+  "li",
+  "main",
+  "ol",
+  "p",
+  "section",
+  "summary",
+  "table",
+  "tr",
+  "ul",
+];
+
+/// Convert an HTML document into AST'd Gemtext
+///
+/// Paragraphs become text lines, `h1` to `h6` headings are clamped to the three
+/// heading levels of Gemtext, `ul` and `ol` lists become (flattened) lists,
+/// blockquotes become quote lines, and `pre` elements become preformatted
+/// blocks, with the language of a `language-*` class as their alt text.
+/// Scripts, styles, and navigation are dropped, and anchors are turned into
+/// link lines placed according to `links`. Lines which would be read back as
+/// Gemtext of another kind, e.g., a paragraph starting with `#`, are led by a
+/// space.
+///
+/// # Example
+///
+/// ```rust
+/// use germ::convert::{LinkPlacement, from_html};
+///
+/// let html = "<nav><a href=\"/\">Home</a></nav><h4>Hi</h4><p>See <a \
+///             href=\"/docs\">the \
+///             docs</a>.</p><ul><li>a<ul><li>b</li></ul></li></ul>";
+///
+/// assert_eq!(
+///   from_html(html, LinkPlacement::Paragraph).to_gemtext(),
+///   "### Hi\nSee the docs.\n=> /docs the docs\n* a\n* b"
+/// );
+/// assert_eq!(
+///   from_html(html, LinkPlacement::Inline).to_gemtext(),
+///   "### Hi\nSee\n=> /docs the docs\n.\n* a\n* b"
+/// );
+/// assert_eq!(
+///   from_html(html, LinkPlacement::Footnotes).to_gemtext(),
+///   "### Hi\nSee the docs [1].\n* a\n* b\n\n=> /docs [1] the docs"
+/// );
+/// ```
+#[must_use]
+pub fn from_html(source: &str, links: LinkPlacement) -> Ast {
+  let mut reader = Reader {
+    links,
+    nodes: vec![],
+    text: String::new(),
+    heading: None,
+    quotes: 0,
+    list: None,
+    lists: 0,
+    preformatted: None,
+    anchor: None,
+    pending: vec![],
+    footnotes: vec![],
+  };
+  let mut rest = source;
+
+  while let Some(start) = rest.find('<') {
+    reader.characters(&rest[..start]);
+    rest = &rest[start..];
+
+    if let Some(comment) = rest.strip_prefix("<!--") {
+      rest = comment.find("-->").map_or("", |end| &comment[end + 3..]);
+    } else if rest.starts_with("<!") || rest.starts_with("<?") {
+      rest = rest.find('>').map_or("", |end| &rest[end + 1..]);
+    } else if let Some((tag, length)) = Tag::parse(rest) {
+      rest = &rest[length..];
+
+      if !tag.closing && DROPPED.contains(&tag.name.as_str()) {
+        rest = skip(rest, &tag.name);
+      } else {
+        reader.tag(&tag);
+      }
+    } else {
+      reader.characters("<");
+      rest = &rest[1..];
+    }
+  }
+
+  reader.characters(rest);
+  reader.flush();
+
+  if reader.list.is_some() {
+    reader.close_list();
+  }
+
+  if !reader.footnotes.is_empty() {
+    if !reader.nodes.is_empty() {
+      reader.nodes.push(Node::Whitespace);
+    }
+
+    reader.nodes.append(&mut reader.footnotes);
+  }
+
+  Ast::from_nodes(reader.nodes.into_iter().map(literal).collect())
+}
+
+struct Reader {
+  links:        LinkPlacement,
+  nodes:        Vec<Node>,
+  /// The text of the current block
+  text:         String,
+  heading:      Option<usize>,
+  quotes:       usize,
+  list:         Option<Vec<String>>,
+  lists:        usize,
+  preformatted: Option<(Option<String>, String)>,
+  anchor:       Option<(String, usize)>,
+  /// The links of the current block, which follow it
+  pending:      Vec<Node>,
+  footnotes:    Vec<Node>,
+}
+
+impl Reader {
+  fn characters(&mut self, raw: &str) {
+    let text = decode(raw);
+
+    if let Some((_, preformatted)) = &mut self.preformatted {
+      preformatted.push_str(&text);
+
+      return;
+    }
+
+    // Runs of whitespace, other than non-breaking spaces, are collapsed into a
+    // single space, like a browser would render them.
+    for character in text.chars() {
+      if character.is_ascii_whitespace() {
+        if !self.text.is_empty() && !self.text.ends_with(' ') {
+          self.text.push(' ');
+        }
+      } else {
+        self.text.push(character);
+      }
+    }
+  }
+
+  fn tag(&mut self, tag: &Tag) {
+    let name = tag.name.as_str();
+
+    match name {
+      "a" if !tag.closing => {
+        self.anchor =
+          tag.attribute("href").map(|href| (href.to_string(), self.text.len()));
+      }
+      "a" => self.close_anchor(),
+      "br" =>
+        if let Some((_, preformatted)) = &mut self.preformatted {
+          preformatted.push('\n');
+        } else if self.list.is_some() {
+          self.characters(" ");
+        } else {
+          self.flush();
+        },
+      "pre" if !tag.closing => {
+        self.flush();
+        self.preformatted = Some((tag.language(), String::new()));
+      }
+      "pre" => {
+        if let Some((alt_text, text)) = self.preformatted.take() {
+          // A newline directly after the opening tag is not part of the
+          // content.
+          let mut text = text.strip_prefix('\n').unwrap_or(&text).to_string();
+
+          if !text.is_empty() && !text.ends_with('\n') {
+            text.push('\n');
+          }
+
+          self.nodes.push(Node::PreformattedText { alt_text, text });
+          self.nodes.append(&mut self.pending);
+        }
+      }
+      "code" if !tag.closing => {
+        if let Some((alt_text @ None, _)) = &mut self.preformatted {
+          *alt_text = tag.language();
+        }
+      }
+      "td" | "th" if tag.closing => self.characters(" "),
+      _ if self.preformatted.is_some() => {}
+      "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+        self.flush();
+        self.heading =
+          (!tag.closing).then(|| usize::from(name.as_bytes()[1] - b'0').min(3));
+      }
+      "ul" | "ol" => {
+        self.flush();
+
+        if tag.closing {
+          self.lists = self.lists.saturating_sub(1);
+
+          if self.lists == 0 {
+            self.close_list();
+          }
+        } else {
+          // Nested lists are flattened into the outermost one.
+          self.lists += 1;
+          self.list.get_or_insert_with(Vec::new);
+        }
+      }
+      "blockquote" => {
+        self.flush();
+        self.quotes = if tag.closing {
+          self.quotes.saturating_sub(1)
+        } else {
+          self.quotes + 1
+        };
+      }
+      name if BLOCKS.contains(&name) => self.flush(),
+      _ => {}
+    }
+  }
+
+  fn close_anchor(&mut self) {
+    let Some((to, start)) = self.anchor.take() else {
+      return;
+    };
+    let text = self.text.get(start..).unwrap_or_default().trim().to_string();
+    let text = (!text.is_empty()).then_some(text);
+
+    match self.links {
+      LinkPlacement::Inline if self.list.is_none() => {
+        self.text.truncate(start);
+        self.flush();
+        self.nodes.push(Node::Link { to, text });
+      }
+      LinkPlacement::Footnotes => {
+        let number = self.footnotes.len() + 1;
+
+        if !(self.text.is_empty() || self.text.ends_with(' ')) {
+          self.text.push(' ');
+        }
+
+        self.text.push_str(&format!("[{number}]"));
+        self.footnotes.push(Node::Link {
+          to,
+          text: Some(text.map_or_else(
+            || format!("[{number}]"),
+            |text| format!("[{number}] {text}"),
+          )),
+        });
+      }
+      _ => self.pending.push(Node::Link { to, text }),
+    }
+  }
+
+  /// End the current block, emitting its text as the node of its kind
+  fn flush(&mut self) {
+    let text = std::mem::take(&mut self.text)
+      .trim_matches(|character: char| character.is_ascii_whitespace())
+      .to_string();
+
+    if let Some((_, start)) = &mut self.anchor {
+      *start = 0;
+    }
+
+    if !text.is_empty() {
+      if let Some(items) = &mut self.list {
+        items.push(text);
+      } else if let Some(level) = self.heading {
+        self.nodes.push(Node::Heading { level, text });
+      } else if self.quotes > 0 {
+        self.nodes.push(Node::Blockquote(text));
+      } else {
+        self.nodes.push(Node::Text(text));
+      }
+    }
+
+    // The links of a list item follow the whole list.
+    if self.list.is_none() {
+      self.nodes.append(&mut self.pending);
+    }
+  }
+
+  fn close_list(&mut self) {
+    if let Some(items) = self.list.take() {
+      if !items.is_empty() {
+        self.nodes.push(Node::List(items));
+      }
+
+      self.nodes.append(&mut self.pending);
+    }
+  }
+}
+
+/// An opening or closing HTML tag
+struct Tag {
+  name:       String,
+  closing:    bool,
+  attributes: Vec<(String, String)>,
+}
+
+impl Tag {
+  /// Parse the tag which `source` starts with, and its length
+  fn parse(source: &str) -> Option<(Self, usize)> {
+    let rest = &source[1..];
+    let (closing, rest) =
+      rest.strip_prefix('/').map_or((false, rest), |rest| (true, rest));
+    let name_length = rest
+      .find(|character: char| {
+        !(character.is_ascii_alphanumeric() || character == '-')
+      })
+      .unwrap_or(rest.len());
+
+    if name_length == 0
+      || !rest.starts_with(|character: char| character.is_ascii_alphabetic())
+    {
+      return None;
+    }
+
+    let name = rest[..name_length].to_ascii_lowercase();
+    let mut attributes = vec![];
+    let mut index = name_length;
+
+    loop {
+      let remaining = &rest[index..];
+      let trimmed = remaining.trim_start_matches(|character: char| {
+        character.is_whitespace() || character == '/'
+      });
+
+      index += remaining.len() - trimmed.len();
+
+      if trimmed.is_empty() {
+        return None;
+      }
+
+      if trimmed.starts_with('>') {
+        return Some((
+          Self { name, closing, attributes },
+          source.len() - rest.len() + index + 1,
+        ));
+      }
+
+      let key_length = trimmed
+        .find(|character: char| {
+          character.is_whitespace() || matches!(character, '=' | '>' | '/')
+        })
+        .unwrap_or(trimmed.len());
+      let key = trimmed[..key_length].to_ascii_lowercase();
+      let after_key = &trimmed[key_length..];
+      let after_equals = after_key.trim_start().strip_prefix('=');
+
+      index += key_length;
+
+      let Some(after_equals) = after_equals else {
+        attributes.push((key, String::new()));
+
+        continue;
+      };
+      let value_start = after_equals.trim_start();
+      let (value, length) = if let Some(quote @ ('"' | '\'')) =
+        value_start.chars().next()
+      {
+        let end = value_start[1..].find(quote)?;
+
+        (&value_start[1..=end], end + 2)
+      } else {
+        let end = value_start
+          .find(|character: char| character.is_whitespace() || character == '>')
+          .unwrap_or(value_start.len());
+
+        (&value_start[..end], end)
+      };
+
+      index += after_key.len() - value_start.len() + length;
+      attributes.push((key, decode(value)));
+    }
+  }
+
+  fn attribute(&self, name: &str) -> Option<&str> {
+    self
+      .attributes
+      .iter()
+      .find(|(key, _)| key == name)
+      .map(|(_, value)| value.as_str())
+  }
+
+  /// The language named by a `language-*` or `lang-*` class
+  fn language(&self) -> Option<String> {
+    self.attribute("class")?.split_whitespace().find_map(|class| {
+      class
+        .strip_prefix("language-")
+        .or_else(|| class.strip_prefix("lang-"))
+        .map(ToString::to_string)
+    })
+  }
+}
+
+/// Skip past the end of the dropped element `name`, whose opening tag `rest`
+/// follows
+fn skip<'a>(rest: &'a str, name: &str) -> &'a str {
+  let closing = format!("</{name}");
+  let mut depth = 1;
+  let mut index = 0;
+  let lowercase = rest.to_ascii_lowercase();
+
+  while let Some(start) = lowercase[index..].find('<') {
+    let start = index + start;
+    let tag = &lowercase[start..];
+
+    if tag.starts_with(&closing) {
+      depth -= 1;
+    } else if tag[1..].starts_with(name) && !matches!(name, "script" | "style")
+    {
+      depth += 1;
+    }
+
+    index = start + 1;
+
+    if depth == 0 {
+      return rest[start..]
+        .find('>')
+        .map_or("", |end| &rest[start + end + 1..]);
+    }
+  }
+
+  ""
+}
+
+/// Decode the character references of HTML text
+fn decode(text: &str) -> String {
+  let mut decoded = String::new();
+  let mut rest = text;
+
+  while let Some(start) = rest.find('&') {
+    decoded.push_str(&rest[..start]);
+    rest = &rest[start..];
+
+    let reference =
+      rest[1..].find(';').filter(|&end| end <= 10).and_then(|end| {
+        let name = &rest[1..=end];
+        let character = match name {
+          "amp" => Some('&'),
+          "lt" => Some('<'),
+          "gt" => Some('>'),
+          "quot" => Some('"'),
+          "apos" => Some('\''),
+          "nbsp" => Some('\u{a0}'),
+          _ => name.strip_prefix('#').and_then(|number| {
+            number
+              .strip_prefix(['x', 'X'])
+              .map_or_else(
+                || number.parse().ok(),
+                |hex| u32::from_str_radix(hex, 16).ok(),
+              )
+              .and_then(char::from_u32)
+          }),
+        };
+
+        character.map(|character| (character, end + 2))
+      });
+
+    if let Some((character, length)) = reference {
+      decoded.push(character);
+      rest = &rest[length..];
+    } else {
+      decoded.push('&');
+      rest = &rest[1..];
+    }
+  }
+
+  decoded.push_str(rest);
+
+  decoded
+}
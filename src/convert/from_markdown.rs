@@ -0,0 +1,780 @@
+// This file is part of Germ <https://github.com/gemrest/germ>.
+// Copyright (C) 2022-2022 Fuwn <contact@fuwn.me>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+//
+// Copyright (C) 2022-2022 Fuwn <contact@fuwn.me>
+// SPDX-License-Identifier: GPL-3.0-only
+
+use {
+  super::literal::literal,
+  crate::ast::{Ast, Node},
+  std::collections::HashMap,
+};
+
+/// Convert Markdown into AST'd Gemtext
+///
+/// Gemtext has no inline markup, so emphasis is dropped, and inline links are
+/// lifted out into link lines after the block they are in, keeping their text
+/// in place. Nested lists are flattened into a single list, headings deeper
+/// than `###` become `###` headings, tables become preformatted blocks, and
+/// the language of a fenced code block becomes the alt text of its
+/// preformatted block. Lines which would be read back as Gemtext of another
+/// kind, e.g., an escaped `\# x`, are led by a space.
+///
+/// # Example
+///
+/// ```rust
+/// use germ::convert::from_markdown;
+///
+/// assert_eq!(
+///   from_markdown(
+///     "#### Hi\n\nSee [the **docs**](/docs).\n\n- a\n  - b\n\n```rust\nfn \
+///      main() {}\n```"
+///   )
+///   .to_gemtext(),
+///   "### Hi\n\nSee the docs.\n=> /docs the docs\n\n* a\n* b\n\n```rust\nfn \
+///    main() {}\n```"
+/// );
+/// ```
+#[must_use]
+pub fn from_markdown(source: &str) -> Ast {
+  let lines = source.lines().collect::<Vec<_>>();
+  let mut reader = Reader {
+    lines:       &lines,
+    index:       0,
+    nodes:       vec![],
+    definitions: lines.iter().filter_map(|line| definition(line)).collect(),
+  };
+
+  reader.read();
+
+  Ast::from_nodes(reader.nodes.into_iter().map(literal).collect())
+}
+
+struct Reader<'a> {
+  lines:       &'a [&'a str],
+  index:       usize,
+  nodes:       Vec<Node>,
+  definitions: HashMap<String, String>,
+}
+
+impl Reader<'_> {
+  fn read(&mut self) {
+    let mut blank = false;
+
+    while let Some(&line) = self.lines.get(self.index) {
+      let trimmed = line.trim_start();
+
+      if trimmed.is_empty() {
+        blank = true;
+        self.index += 1;
+
+        continue;
+      }
+
+      if definition(line).is_some() || thematic_break(line) {
+        self.index += 1;
+
+        continue;
+      }
+
+      // Runs of blank lines between blocks are kept as a single whitespace
+      // line.
+      if blank && !self.nodes.is_empty() {
+        self.nodes.push(Node::Whitespace);
+      }
+
+      blank = false;
+
+      if let Some((fence, info)) = fence(line) {
+        self.fenced(line, fence, info);
+      } else if indentation(line) >= 4 {
+        self.indented();
+      } else if let Some((level, text)) = atx_heading(line) {
+        let mut lifted = vec![];
+        let text = self.inline(text, &mut lifted);
+
+        self.nodes.push(Node::Heading { level: level.min(3), text });
+        self.nodes.extend(lifted);
+        self.index += 1;
+      } else if self.is_table(self.index) {
+        self.table();
+      } else if blockquote(line).is_some() {
+        self.blockquote();
+      } else if list_item(line).is_some() {
+        self.list();
+      } else {
+        self.paragraph();
+      }
+    }
+  }
+
+  fn fenced(&mut self, opening: &str, fence: &str, info: &str) {
+    let indentation = indentation(opening);
+    let mut text = String::new();
+
+    self.index += 1;
+
+    while let Some(&line) = self.lines.get(self.index) {
+      self.index += 1;
+
+      if fence_closes(line, fence) {
+        break;
+      }
+
+      text.push_str(dedent(line, indentation));
+      text.push('\n');
+    }
+
+    self.nodes.push(Node::PreformattedText {
+      alt_text: info.split_whitespace().next().map(ToString::to_string),
+      text,
+    });
+  }
+
+  fn indented(&mut self) {
+    let mut lines = vec![];
+
+    while let Some(&line) = self.lines.get(self.index) {
+      if !line.trim().is_empty() && indentation(line) < 4 {
+        break;
+      }
+
+      lines.push(dedent(line, 4));
+      self.index += 1;
+    }
+
+    // Trailing blank lines separate the code block from what follows it.
+    while lines.last().map_or(false, |line| line.trim().is_empty()) {
+      lines.pop();
+      self.index -= 1;
+    }
+
+    self.nodes.push(Node::PreformattedText {
+      alt_text: None,
+      text:     lines.iter().fold(String::new(), |mut text, line| {
+        text.push_str(line);
+        text.push('\n');
+
+        text
+      }),
+    });
+  }
+
+  fn is_table(&self, index: usize) -> bool {
+    self.lines.get(index).map_or(false, |line| line.contains('|'))
+      && self.lines.get(index + 1).map_or(false, |line| delimiter_row(line))
+  }
+
+  fn table(&mut self) {
+    let mut text = String::new();
+
+    while let Some(&line) = self.lines.get(self.index) {
+      if line.trim().is_empty() || !line.contains('|') {
+        break;
+      }
+
+      text.push_str(line.trim());
+      text.push('\n');
+      self.index += 1;
+    }
+
+    self.nodes.push(Node::PreformattedText { alt_text: None, text });
+  }
+
+  fn blockquote(&mut self) {
+    let mut paragraphs = vec![vec![]];
+
+    while let Some(&line) = self.lines.get(self.index) {
+      let content = match blockquote(line) {
+        Some(content) => content,
+        // Lines which continue the paragraph of a blockquote lazily belong
+        // to it.
+        None
+          if !line.trim().is_empty()
+            && !self.interrupts(self.index)
+            && paragraphs.last().map_or(false, |lines| !lines.is_empty()) =>
+          line.trim(),
+        None => break,
+      };
+
+      if content.trim().is_empty() {
+        paragraphs.push(vec![]);
+      } else if let Some(lines) = paragraphs.last_mut() {
+        lines.push(content);
+      }
+
+      self.index += 1;
+    }
+
+    let mut lifted = vec![];
+
+    for lines in paragraphs.into_iter().filter(|lines| !lines.is_empty()) {
+      for text in joined(&lines) {
+        let text = self.inline(&text, &mut lifted);
+
+        self.nodes.push(Node::Blockquote(text));
+      }
+    }
+
+    self.nodes.extend(lifted);
+  }
+
+  fn list(&mut self) {
+    let mut items: Vec<String> = vec![];
+
+    while let Some(&line) = self.lines.get(self.index) {
+      if line.trim().is_empty() {
+        // A blank line only ends the list if the list does not go on after it.
+        let next =
+          self.lines[self.index..].iter().find(|line| !line.trim().is_empty());
+
+        if next.map_or(false, |next| {
+          list_item(next).is_some() || indentation(next) >= 2
+        }) {
+          self.index += 1;
+
+          continue;
+        }
+
+        break;
+      }
+
+      if let Some(item) = list_item(line).filter(|_| !thematic_break(line)) {
+        items.push(item.to_string());
+      } else if indentation(line) >= 2 || !self.interrupts(self.index) {
+        // Continuation lines of an item, including the paragraphs of nested
+        // blocks, are joined onto it.
+        if let Some(item) = items.last_mut() {
+          item.push(' ');
+          item.push_str(line.trim());
+        }
+      } else {
+        break;
+      }
+
+      self.index += 1;
+    }
+
+    let mut lifted = vec![];
+    let items =
+      items.iter().map(|item| self.inline(item.trim(), &mut lifted)).collect();
+
+    self.nodes.push(Node::List(items));
+    self.nodes.extend(lifted);
+  }
+
+  fn paragraph(&mut self) {
+    let mut lines = vec![self.lines[self.index]];
+
+    self.index += 1;
+
+    while let Some(&line) = self.lines.get(self.index) {
+      let underline = line.trim();
+
+      // A paragraph underlined by `=` or `-` is a setext heading.
+      if !underline.is_empty()
+        && (underline.chars().all(|character| character == '=')
+          || underline.chars().all(|character| character == '-'))
+      {
+        let mut lifted = vec![];
+        let text = self.inline(&joined(&lines).join(" "), &mut lifted);
+
+        self.nodes.push(Node::Heading {
+          level: if underline.starts_with('=') { 1 } else { 2 },
+          text,
+        });
+        self.nodes.extend(lifted);
+        self.index += 1;
+
+        return;
+      }
+
+      if underline.is_empty()
+        || self.interrupts(self.index)
+        || self.is_table(self.index)
+      {
+        break;
+      }
+
+      lines.push(line);
+      self.index += 1;
+    }
+
+    let mut lifted = vec![];
+
+    for text in joined(&lines) {
+      let text = self.inline(&text, &mut lifted);
+
+      if !text.is_empty() {
+        self.nodes.push(Node::Text(text));
+      }
+    }
+
+    self.nodes.extend(lifted);
+  }
+
+  /// Whether the line at `index` starts a block which interrupts a paragraph
+  fn interrupts(&self, index: usize) -> bool {
+    self.lines.get(index).map_or(false, |&line| {
+      fence(line).is_some()
+        || atx_heading(line).is_some()
+        || blockquote(line).is_some()
+        || list_item(line).is_some()
+        || thematic_break(line)
+    })
+  }
+
+  /// Strip the inline markup of `text`, collecting its lifted into `lifted`
+  fn inline(&self, text: &str, lifted: &mut Vec<Node>) -> String {
+    let mut plain = String::new();
+    let mut previous = None;
+    let mut index = 0;
+
+    while let Some(character) = text[index..].chars().next() {
+      let rest = &text[index..];
+      let length = match character {
+        '\\' => match rest[1..].chars().next() {
+          Some(escaped) if escaped.is_ascii_punctuation() => {
+            plain.push(escaped);
+
+            2
+          }
+          _ => {
+            plain.push('\\');
+
+            1
+          }
+        },
+        '`' => {
+          let run = rest.len() - rest.trim_start_matches('`').len();
+
+          if let Some((code, length)) = code_span(&rest[run..], run) {
+            plain.push_str(code);
+
+            run + length
+          } else {
+            plain.push_str(&rest[..run]);
+
+            run
+          }
+        }
+        '!' if rest[1..].starts_with('[') =>
+          self.link(&rest[1..], lifted, &mut plain).map_or_else(
+            || {
+              plain.push('!');
+
+              1
+            },
+            |length| length + 1,
+          ),
+        '[' => self.link(rest, lifted, &mut plain).unwrap_or_else(|| {
+          plain.push('[');
+
+          1
+        }),
+        '<' =>
+          if let Some((to, length)) = autolink(rest) {
+            plain.push_str(to);
+            lifted.push(Node::Link {
+              to:   if to.contains(':') {
+                to.to_string()
+              } else {
+                format!("mailto:{to}")
+              },
+              text: None,
+            });
+
+            length
+          } else {
+            plain.push('<');
+
+            1
+          },
+        '*' | '_' | '~' => {
+          let run = rest.len() - rest.trim_start_matches(character).len();
+          let next = rest[run..].chars().next();
+          let opens = next.map_or(false, |next| !next.is_whitespace());
+          let closes =
+            previous.map_or(false, |previous: char| !previous.is_whitespace());
+          // Underscores within words, like those of `snake_case`, are not
+          // emphasis.
+          let emphasis = match character {
+            '_' =>
+              (opens && !previous.map_or(false, char::is_alphanumeric))
+                || (closes && !next.map_or(false, char::is_alphanumeric)),
+            '~' => run == 2 && (opens || closes),
+            _ => opens || closes,
+          };
+
+          if !emphasis {
+            plain.push_str(&rest[..run]);
+          }
+
+          run
+        }
+        character => {
+          plain.push(character);
+
+          character.len_utf8()
+        }
+      };
+
+      previous = text[..index + length].chars().next_back();
+      index += length;
+    }
+
+    plain
+  }
+
+  /// Read the link or image which `text` starts with, pushing its text onto
+  /// `plain` and the link itself onto `lifted`, and returning its length
+  fn link(
+    &self,
+    text: &str,
+    lifted: &mut Vec<Node>,
+    plain: &mut String,
+  ) -> Option<usize> {
+    let close = closing_bracket(text)?;
+    let label = &text[1..close];
+    let rest = &text[close + 1..];
+    let (to, length) = if let Some(destination) = rest.strip_prefix('(') {
+      let (to, length) = destination_of(destination)?;
+
+      (to.to_string(), close + 2 + length)
+    } else if let Some(reference) = rest.strip_prefix('[') {
+      let end = reference.find(']')?;
+      let reference = if end == 0 { label } else { &reference[..end] };
+
+      (self.definitions.get(&normalise(reference))?.clone(), close + 3 + end)
+    } else {
+      (self.definitions.get(&normalise(label))?.clone(), close + 1)
+    };
+    let mut nested = vec![];
+    let label = self.inline(label, &mut nested);
+
+    plain.push_str(&label);
+    lifted.push(Node::Link {
+      text: (!label.is_empty() && label != to).then_some(label),
+      to,
+    });
+    lifted.extend(nested);
+
+    Some(length)
+  }
+}
+
+/// Split the lines of a paragraph at its hard line breaks, joining the lines
+/// in between
+fn joined(lines: &[&str]) -> Vec<String> {
+  let mut texts = vec![];
+  let mut text = String::new();
+
+  for line in lines {
+    let hard_break = line.ends_with("  ") || line.ends_with('\\');
+    let line = line.trim();
+    let line = if hard_break { line.trim_end_matches('\\') } else { line };
+
+    if !text.is_empty() {
+      text.push(' ');
+    }
+
+    text.push_str(line.trim_end());
+
+    // A line which is nothing but a hard line break ends no text.
+    if hard_break && !text.is_empty() {
+      texts.push(std::mem::take(&mut text));
+    }
+  }
+
+  if !text.is_empty() {
+    texts.push(text);
+  }
+
+  texts
+}
+
+/// The width of the leading whitespace of `line`, with tabs counted as four
+/// spaces
+fn indentation(line: &str) -> usize {
+  line
+    .chars()
+    .take_while(|character| character.is_whitespace())
+    .map(|character| if character == '\t' { 4 } else { 1 })
+    .sum()
+}
+
+/// Strip the leading whitespace of `line` which fits within `width` columns,
+/// with tabs counted as four spaces
+fn dedent(line: &str, mut width: usize) -> &str {
+  let mut rest = line;
+
+  while let Some(character) =
+    rest.chars().next().filter(|character| character.is_whitespace())
+  {
+    let columns = if character == '\t' { 4 } else { 1 };
+
+    if columns > width {
+      break;
+    }
+
+    width -= columns;
+    rest = &rest[character.len_utf8()..];
+  }
+
+  rest
+}
+
+fn fence(line: &str) -> Option<(&str, &str)> {
+  if indentation(line) >= 4 {
+    return None;
+  }
+
+  let trimmed = line.trim_start();
+  let character = trimmed.chars().next().filter(|&c| c == '`' || c == '~')?;
+  let run = trimmed.len() - trimmed.trim_start_matches(character).len();
+  let info = trimmed[run..].trim();
+
+  (run >= 3 && !(character == '`' && info.contains('`')))
+    .then(|| (&trimmed[..run], info))
+}
+
+fn fence_closes(line: &str, fence: &str) -> bool {
+  let trimmed = line.trim();
+
+  indentation(line) < 4
+    && trimmed.len() >= fence.len()
+    && trimmed.starts_with(fence)
+    && trimmed.trim_start_matches(&fence[..1]).is_empty()
+}
+
+fn atx_heading(line: &str) -> Option<(usize, &str)> {
+  if indentation(line) >= 4 {
+    return None;
+  }
+
+  let trimmed = line.trim();
+  let level = trimmed.len() - trimmed.trim_start_matches('#').len();
+  let rest = &trimmed[level..];
+
+  if !(1..=6).contains(&level) || !(rest.is_empty() || rest.starts_with(' ')) {
+    return None;
+  }
+
+  // A closing sequence of `#`s is not a part of the heading.
+  let text = rest.trim();
+  let without_closing = text.trim_end_matches('#');
+
+  Some((
+    level,
+    if without_closing.is_empty() || without_closing.ends_with(' ') {
+      without_closing.trim_end()
+    } else {
+      text
+    },
+  ))
+}
+
+fn thematic_break(line: &str) -> bool {
+  let trimmed = line.trim();
+
+  indentation(line) < 4
+    && ['-', '*', '_'].iter().any(|&character| {
+      trimmed.chars().filter(|&c| c == character).count() >= 3
+        && trimmed.chars().all(|c| c == character || c == ' ' || c == '\t')
+    })
+}
+
+fn blockquote(line: &str) -> Option<&str> {
+  if indentation(line) >= 4 {
+    return None;
+  }
+
+  let mut content = line.trim_start().strip_prefix('>')?;
+
+  // Nested blockquotes are flattened into their outermost one.
+  loop {
+    content = content.strip_prefix(' ').unwrap_or(content);
+
+    match content.strip_prefix('>') {
+      Some(nested) => content = nested,
+      None => return Some(content),
+    }
+  }
+}
+
+fn list_item(line: &str) -> Option<&str> {
+  let trimmed = line.trim_start();
+  let digits = trimmed.len()
+    - trimmed.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+  let marker = if digits > 0 {
+    (digits <= 9 && trimmed[digits..].starts_with(['.', ')']))
+      .then_some(digits + 1)?
+  } else {
+    trimmed.starts_with(['-', '*', '+']).then_some(1)?
+  };
+  let rest = &trimmed[marker..];
+
+  (rest.is_empty() || rest.starts_with([' ', '\t'])).then(|| rest.trim())
+}
+
+fn delimiter_row(line: &str) -> bool {
+  let trimmed = line.trim().trim_start_matches('|').trim_end_matches('|');
+
+  !trimmed.is_empty()
+    && trimmed.split('|').all(|cell| {
+      let cell = cell.trim().trim_start_matches(':').trim_end_matches(':');
+
+      !cell.is_empty() && cell.chars().all(|character| character == '-')
+    })
+}
+
+/// The normalised label and destination of a link reference definition, e.g.,
+/// `[label]: /destination`
+fn definition(line: &str) -> Option<(String, String)> {
+  if indentation(line) >= 4 {
+    return None;
+  }
+
+  let rest = line.trim_start().strip_prefix('[')?;
+  let (label, destination) = rest.split_once("]:")?;
+  let destination = destination.split_whitespace().next()?;
+
+  Some((
+    normalise(label),
+    destination
+      .strip_prefix('<')
+      .and_then(|destination| destination.strip_suffix('>'))
+      .unwrap_or(destination)
+      .to_string(),
+  ))
+}
+
+fn normalise(label: &str) -> String {
+  label.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+}
+
+/// The content of a code span opened by `run` backticks, and the length of
+/// the rest of it
+fn code_span(text: &str, run: usize) -> Option<(&str, usize)> {
+  let mut index = 0;
+
+  while let Some(start) = text[index..].find('`') {
+    let start = index + start;
+    let length =
+      text[start..].len() - text[start..].trim_start_matches('`').len();
+
+    if length == run {
+      let code = &text[..start];
+      let code =
+        if code.starts_with(' ') && code.ends_with(' ') && code.trim() != "" {
+          &code[1..code.len() - 1]
+        } else {
+          code
+        };
+
+      return Some((code, start + length));
+    }
+
+    index = start + length;
+  }
+
+  None
+}
+
+/// The index of the bracket which closes the one `text` starts with
+fn closing_bracket(text: &str) -> Option<usize> {
+  let mut depth = 0;
+  let mut escaped = false;
+
+  for (index, character) in text.char_indices() {
+    match character {
+      _ if escaped => escaped = false,
+      '\\' => escaped = true,
+      '[' => depth += 1,
+      ']' => {
+        depth -= 1;
+
+        if depth == 0 {
+          return Some(index);
+        }
+      }
+      _ => {}
+    }
+  }
+
+  None
+}
+
+/// The destination of an inline link, and the length of the rest of the link
+/// up to and including its closing parenthesis
+fn destination_of(text: &str) -> Option<(&str, usize)> {
+  let start = text.len() - text.trim_start().len();
+  let rest = &text[start..];
+  let (to, mut index) = if let Some(angled) = rest.strip_prefix('<') {
+    let end = angled.find('>')?;
+
+    (&angled[..end], start + end + 2)
+  } else {
+    let mut depth = 0;
+    let end = rest
+      .char_indices()
+      .find(|&(_, character)| match character {
+        '(' => {
+          depth += 1;
+
+          false
+        }
+        ')' if depth == 0 => true,
+        ')' => {
+          depth -= 1;
+
+          false
+        }
+        character => character.is_whitespace(),
+      })
+      .map_or(rest.len(), |(end, _)| end);
+
+    (&rest[..end], start + end)
+  };
+
+  // A title may follow the destination, which is dropped.
+  let after = &text[index..];
+  let after_trimmed = after.trim_start();
+
+  index += after.len() - after_trimmed.len();
+
+  if let Some(quote) =
+    after_trimmed.chars().next().filter(|c| matches!(c, '"' | '\'' | '('))
+  {
+    let close = if quote == '(' { ')' } else { quote };
+    let end = after_trimmed[1..].find(close)?;
+    let rest = &after_trimmed[end + 2..];
+
+    index += end + 2 + (rest.len() - rest.trim_start().len());
+  }
+
+  text[index..].starts_with(')').then_some((to, index + 1))
+}
+
+fn autolink(text: &str) -> Option<(&str, usize)> {
+  let end = text.find('>')?;
+  let to = &text[1..end];
+
+  (!to.is_empty()
+    && !to.contains(char::is_whitespace)
+    && !to.contains('<')
+    && (to.contains(':') || to.contains('@')))
+  .then_some((to, end + 1))
+}
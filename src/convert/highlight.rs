@@ -0,0 +1,534 @@
+// This file is part of Germ <https://github.com/gemrest/germ>.
+// Copyright (C) 2022-2022 Fuwn <contact@fuwn.me>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+//
+// Copyright (C) 2022-2022 Fuwn <contact@fuwn.me>
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Highlight the syntax of preformatted text in HTML
+//!
+//! The alt text of a preformatted text block is read as a hint of its
+//! language, so a block opened by ```` ```rust ```` is highlighted as Rust.
+//! Each token is wrapped in a `<span>` classed by its [`TokenKind`], which a
+//! [`Theme`] can style.
+//!
+//! # Example
+//!
+//! ```rust
+//! use germ::{
+//!   ast::Ast,
+//!   convert::{highlight::Highlighter, HtmlOptions},
+//! };
+//!
+//! assert_eq!(
+//!   Highlighter::new().to_html(
+//!     &Ast::from_string("```rust\nlet x = 1;\n```"),
+//!     &HtmlOptions::new()
+//!   ),
+//!   "<pre><code class=\"language-rust\"><span class=\"keyword\">let</span> x \
+//!    = <span class=\"number\">1</span>;\n</code></pre>"
+//! );
+//! ```
+
+use {
+  super::HtmlOptions,
+  crate::{ast::borrowed, escape::escape},
+};
+
+/// The kinds of tokens which are highlighted
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TokenKind {
+  /// A reserved word of the language
+  Keyword,
+  /// A string or character literal
+  String,
+  /// A numeric literal
+  Number,
+  /// A line or block comment
+  Comment,
+}
+
+impl TokenKind {
+  const ALL: [Self; 4] =
+    [Self::Keyword, Self::String, Self::Number, Self::Comment];
+
+  const fn index(self) -> usize {
+    match self {
+      Self::Keyword => 0,
+      Self::String => 1,
+      Self::Number => 2,
+      Self::Comment => 3,
+    }
+  }
+}
+
+/// The syntax of a language, as far as it is highlighted
+///
+/// # Example
+///
+/// ```rust
+/// use germ::convert::highlight::{Highlighter, Language};
+///
+/// let highlighter = Highlighter::new().language(
+///   Language::new(["lua"])
+///     .keywords(["local", "function", "end"])
+///     .line_comment("--")
+///     .quotes(['"', '\'']),
+/// );
+///
+/// assert_eq!(
+///   highlighter.highlight("lua", "local x -- x").unwrap(),
+///   "<span class=\"keyword\">local</span> x <span class=\"comment\">-- \
+///    x</span>"
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Language {
+  names:         Vec<String>,
+  keywords:      Vec<String>,
+  line_comment:  Option<String>,
+  block_comment: Option<(String, String)>,
+  quotes:        Vec<char>,
+}
+
+impl Language {
+  /// Create a new `Language` with no syntax, known by any of `names` in alt
+  /// text
+  #[must_use]
+  pub fn new<S: Into<String>>(names: impl IntoIterator<Item = S>) -> Self {
+    Self {
+      names:         names.into_iter().map(Into::into).collect(),
+      keywords:      vec![],
+      line_comment:  None,
+      block_comment: None,
+      quotes:        vec![],
+    }
+  }
+
+  /// Set the reserved words of the `Language`
+  #[must_use]
+  pub fn keywords<S: Into<String>>(
+    mut self,
+    keywords: impl IntoIterator<Item = S>,
+  ) -> Self {
+    self.keywords = keywords.into_iter().map(Into::into).collect();
+
+    self
+  }
+
+  /// Set the marker which comments out the rest of a line, e.g., `//`
+  ///
+  /// An empty marker leaves the `Language` without line comments.
+  #[must_use]
+  pub fn line_comment(mut self, marker: impl Into<String>) -> Self {
+    self.line_comment = Some(marker.into()).filter(|marker| !marker.is_empty());
+
+    self
+  }
+
+  /// Set the markers which open and close a block comment, e.g., `/*` and `*/`
+  ///
+  /// Empty markers leave the `Language` without block comments.
+  #[must_use]
+  pub fn block_comment(
+    mut self,
+    open: impl Into<String>,
+    close: impl Into<String>,
+  ) -> Self {
+    self.block_comment = Some((open.into(), close.into()))
+      .filter(|(open, close)| !open.is_empty() && !close.is_empty());
+
+    self
+  }
+
+  /// Set the characters which delimit string literals
+  #[must_use]
+  pub fn quotes(mut self, quotes: impl IntoIterator<Item = char>) -> Self {
+    self.quotes = quotes.into_iter().collect();
+
+    self
+  }
+
+  /// The names by which the `Language` is known in alt text
+  #[must_use]
+  pub fn names(&self) -> &[String] { &self.names }
+
+  fn is_named(&self, name: &str) -> bool {
+    self.names.iter().any(|known| known.eq_ignore_ascii_case(name))
+  }
+
+  fn rust() -> Self {
+    Self::new(["rust", "rs"])
+      .keywords([
+        "as", "async", "await", "break", "const", "continue", "crate", "dyn",
+        "else", "enum", "extern", "false", "fn", "for", "if", "impl", "in",
+        "let", "loop", "match", "mod", "move", "mut", "pub", "ref", "return",
+        "self", "Self", "static", "struct", "super", "trait", "true", "type",
+        "unsafe", "use", "where", "while",
+      ])
+      .line_comment("//")
+      .block_comment("/*", "*/")
+      .quotes(['"'])
+  }
+
+  fn python() -> Self {
+    Self::new(["python", "py"])
+      .keywords([
+        "False", "None", "True", "and", "as", "assert", "async", "await",
+        "break", "class", "continue", "def", "del", "elif", "else", "except",
+        "finally", "for", "from", "global", "if", "import", "in", "is",
+        "lambda", "nonlocal", "not", "or", "pass", "raise", "return", "try",
+        "while", "with", "yield",
+      ])
+      .line_comment("#")
+      .quotes(['"', '\''])
+  }
+
+  fn javascript() -> Self {
+    Self::new(["javascript", "js", "typescript", "ts"])
+      .keywords([
+        "async",
+        "await",
+        "break",
+        "case",
+        "catch",
+        "class",
+        "const",
+        "continue",
+        "default",
+        "delete",
+        "do",
+        "else",
+        "export",
+        "extends",
+        "false",
+        "finally",
+        "for",
+        "function",
+        "if",
+        "import",
+        "in",
+        "instanceof",
+        "let",
+        "new",
+        "null",
+        "return",
+        "switch",
+        "this",
+        "throw",
+        "true",
+        "try",
+        "typeof",
+        "undefined",
+        "var",
+        "void",
+        "while",
+        "yield",
+      ])
+      .line_comment("//")
+      .block_comment("/*", "*/")
+      .quotes(['"', '\'', '`'])
+  }
+
+  fn c() -> Self {
+    Self::new(["c", "h", "cpp", "c++"])
+      .keywords([
+        "auto", "break", "case", "char", "const", "continue", "default", "do",
+        "double", "else", "enum", "extern", "float", "for", "goto", "if",
+        "int", "long", "register", "return", "short", "signed", "sizeof",
+        "static", "struct", "switch", "typedef", "union", "unsigned", "void",
+        "volatile", "while",
+      ])
+      .line_comment("//")
+      .block_comment("/*", "*/")
+      .quotes(['"', '\''])
+  }
+
+  fn shell() -> Self {
+    Self::new(["sh", "shell", "bash", "zsh"])
+      .keywords([
+        "case", "do", "done", "elif", "else", "esac", "export", "fi", "for",
+        "function", "if", "in", "local", "return", "then", "until", "while",
+      ])
+      .line_comment("#")
+      .quotes(['"', '\''])
+  }
+}
+
+/// The classes, and optionally the colours, of each [`TokenKind`]
+///
+/// # Example
+///
+/// ```rust
+/// use germ::convert::highlight::{Theme, TokenKind};
+///
+/// let theme = Theme::new()
+///   .class(TokenKind::Keyword, "kw")
+///   .colour(TokenKind::Keyword, "#c678dd");
+///
+/// assert_eq!(theme.class_name(TokenKind::Keyword), "kw");
+/// assert_eq!(theme.to_css(), ".kw { color: #c678dd; }\n");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Theme {
+  classes: [String; 4],
+  colours: [Option<String>; 4],
+}
+
+impl Default for Theme {
+  fn default() -> Self { Self::new() }
+}
+
+impl Theme {
+  /// Create a new `Theme` which classes each [`TokenKind`] by its name, e.g.,
+  /// `keyword`, and does not colour any
+  #[must_use]
+  pub fn new() -> Self {
+    Self {
+      classes: [
+        "keyword".to_string(),
+        "string".to_string(),
+        "number".to_string(),
+        "comment".to_string(),
+      ],
+      colours: Default::default(),
+    }
+  }
+
+  /// Set the class of the spans of a [`TokenKind`]
+  #[must_use]
+  pub fn class(mut self, kind: TokenKind, class: impl Into<String>) -> Self {
+    self.classes[kind.index()] = class.into();
+
+    self
+  }
+
+  /// Set the CSS colour of a [`TokenKind`], for [`Theme::to_css`]
+  #[must_use]
+  pub fn colour(mut self, kind: TokenKind, colour: impl Into<String>) -> Self {
+    self.colours[kind.index()] = Some(colour.into());
+
+    self
+  }
+
+  /// The class of the spans of a [`TokenKind`]
+  #[must_use]
+  pub fn class_name(&self, kind: TokenKind) -> &str {
+    &self.classes[kind.index()]
+  }
+
+  /// Emit a stylesheet which colours each coloured [`TokenKind`] by its class
+  #[must_use]
+  pub fn to_css(&self) -> String {
+    TokenKind::ALL
+      .iter()
+      .filter_map(|&kind| {
+        self.colours[kind.index()].as_ref().map(|colour| {
+          format!(".{} {{ color: {colour}; }}\n", self.class_name(kind))
+        })
+      })
+      .collect()
+  }
+}
+
+/// Highlights preformatted text blocks by the language named in their alt
+/// text
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Highlighter {
+  languages: Vec<Language>,
+  theme:     Theme,
+}
+
+impl Default for Highlighter {
+  fn default() -> Self { Self::new() }
+}
+
+impl Highlighter {
+  /// Create a new `Highlighter` which knows Rust, Python, JavaScript, C, and
+  /// shell, using the default [`Theme`]
+  #[must_use]
+  pub fn new() -> Self {
+    Self {
+      languages: vec![
+        Language::rust(),
+        Language::python(),
+        Language::javascript(),
+        Language::c(),
+        Language::shell(),
+      ],
+      theme:     Theme::new(),
+    }
+  }
+
+  /// Add a [`Language`], which takes precedence over the known languages of
+  /// the same name
+  #[must_use]
+  pub fn language(mut self, language: Language) -> Self {
+    self.languages.insert(0, language);
+
+    self
+  }
+
+  /// Replace the known languages
+  #[must_use]
+  pub fn languages(
+    mut self,
+    languages: impl IntoIterator<Item = Language>,
+  ) -> Self {
+    self.languages = languages.into_iter().collect();
+
+    self
+  }
+
+  /// Set the [`Theme`] of the spans
+  #[must_use]
+  pub fn theme(mut self, theme: Theme) -> Self {
+    self.theme = theme;
+
+    self
+  }
+
+  /// Find the known [`Language`] named by the first word of an alt text
+  #[must_use]
+  pub fn find(&self, alt_text: &str) -> Option<&Language> {
+    let name = alt_text.split_whitespace().next()?;
+
+    self.languages.iter().find(|language| language.is_named(name))
+  }
+
+  /// Highlight `text` as the language named by `alt_text`, or `None` if the
+  /// language is unknown
+  ///
+  /// The text of the tokens is HTML-escaped.
+  #[must_use]
+  pub fn highlight(&self, alt_text: &str, text: &str) -> Option<String> {
+    let language = self.find(alt_text)?;
+    let mut html = String::new();
+    let mut rest = text;
+
+    while let Some(character) = rest.chars().next() {
+      let (kind, length) = token(language, rest, character);
+      // Every token takes at least one character, so that a line comment
+      // marker which starts with a line break can't stall the highlighter.
+      let (token, remaining) = rest.split_at(length.max(character.len_utf8()));
+
+      match kind {
+        Some(kind) => html.push_str(&format!(
+          "<span class=\"{}\">{}</span>",
+          self.theme.class_name(kind),
+          escape(token)
+        )),
+        None => html.push_str(&escape(token)),
+      }
+
+      rest = remaining;
+    }
+
+    Some(html)
+  }
+
+  /// Convert AST'd Gemtext into HTML as configured by `options`, highlighting
+  /// the preformatted text blocks of known languages
+  ///
+  /// Preformatted text blocks of unknown languages, or without alt text, are
+  /// converted as they are by [`HtmlOptions::to_html`].
+  #[must_use]
+  pub fn to_html<'a>(
+    &self,
+    source: impl Into<borrowed::Ast<'a>>,
+    options: &HtmlOptions,
+  ) -> String {
+    super::html::convert_with(source.into(), options, &|alt_text, text| {
+      self.highlight(alt_text, text).map(|highlighted| {
+        format!(
+          "<code class=\"language-{}\">{highlighted}</code>",
+          escape(
+            &alt_text
+              .split_whitespace()
+              .next()
+              .unwrap_or_default()
+              .to_lowercase()
+          )
+        )
+      })
+    })
+  }
+}
+
+/// The kind and byte length of the token which `text` starts with
+fn token(
+  language: &Language,
+  text: &str,
+  first: char,
+) -> (Option<TokenKind>, usize) {
+  if let Some((open, close)) = &language.block_comment {
+    if text.starts_with(open.as_str()) {
+      return (
+        Some(TokenKind::Comment),
+        text[open.len()..]
+          .find(close.as_str())
+          .map_or(text.len(), |end| open.len() + end + close.len()),
+      );
+    }
+  }
+
+  if let Some(marker) = &language.line_comment {
+    if text.starts_with(marker.as_str()) {
+      return (Some(TokenKind::Comment), text.find('\n').unwrap_or(text.len()));
+    }
+  }
+
+  if language.quotes.contains(&first) {
+    let mut escaped = false;
+
+    for (index, character) in text.char_indices().skip(1) {
+      if escaped {
+        escaped = false;
+      } else if character == '\\' {
+        escaped = true;
+      } else if character == first {
+        return (Some(TokenKind::String), index + character.len_utf8());
+      }
+    }
+
+    return (Some(TokenKind::String), text.len());
+  }
+
+  let word = text
+    .find(|character: char| !(character.is_alphanumeric() || character == '_'))
+    .unwrap_or(text.len());
+
+  if first.is_ascii_digit() {
+    let number = text
+      .find(|character: char| {
+        !(character.is_ascii_alphanumeric()
+          || character == '_'
+          || character == '.')
+      })
+      .unwrap_or(text.len());
+
+    (Some(TokenKind::Number), number)
+  } else if word > 0 {
+    (
+      language
+        .keywords
+        .iter()
+        .any(|keyword| *keyword == text[..word])
+        .then_some(TokenKind::Keyword),
+      word,
+    )
+  } else {
+    (None, first.len_utf8())
+  }
+}
@@ -16,51 +16,449 @@
 // Copyright (C) 2022-2022 Fuwn <contact@fuwn.me>
 // SPDX-License-Identifier: GPL-3.0-only
 
-use crate::ast::Node;
-
-pub fn convert(source: &[Node]) -> String {
-  let mut html = String::new();
-
-  // Since we have an AST tree of the Gemtext, it is very easy to convert from
-  // this AST tree to an alternative markup format.
-  for node in source {
-    match node {
-      Node::Text(text) => html.push_str(&format!("<p>{text}</p>")),
-      Node::Link { to, text } => {
-        html.push_str(&format!(
-          "<a href=\"{}\">{}</a><br>",
-          to,
-          text.clone().unwrap_or_else(|| to.clone())
-        ));
-      }
-      Node::Heading { level, text } => {
-        html.push_str(&format!(
-          "<{}>{}</{0}>",
-          match level {
-            1 => "h1",
-            2 => "h2",
-            3 => "h3",
-            _ => "p",
-          },
-          text
-        ));
+use {
+  super::{render_with, sink::Sink, LinkRuns, NodeKind, Renderer},
+  crate::{
+    ast::{borrowed, Slugger},
+    escape::escape,
+  },
+  std::{borrow::Cow, fmt},
+};
+
+/// Link schemes which run code, or embed content, when followed
+const DANGEROUS_SCHEMES: [&str; 3] = ["javascript:", "vbscript:", "data:"];
+
+pub fn convert(source: borrowed::Ast<'_>, options: &HtmlOptions) -> String {
+  convert_with(source, options, &|_, _| None)
+}
+
+/// Convert Gemtext into HTML, converting the text of each preformatted text
+/// block with alt text into the `<pre>` element's content by `preformatted`
+/// first, and as it is if that gives back `None`
+pub fn convert_with(
+  source: borrowed::Ast<'_>,
+  options: &HtmlOptions,
+  preformatted: &dyn Fn(&str, &str) -> Option<String>,
+) -> String {
+  let mut output = String::new();
+
+  render_with(source, &mut Html::new(&mut output, options, preformatted));
+
+  output
+}
+
+/// How Gemtext is converted into HTML
+///
+/// By default, `HtmlOptions` converts Gemtext into an escaped HTML fragment,
+/// just like [`Target::HTML`](super::Target::HTML).
+///
+/// # Example
+///
+/// ```rust
+/// use germ::{
+///   ast::Ast,
+///   convert::{HtmlOptions, LinkRuns, NodeKind},
+/// };
+///
+/// let options = HtmlOptions::new()
+///   .document(true)
+///   .lang("en")
+///   .stylesheet("/style.css")
+///   .class(NodeKind::Link, "link")
+///   .links(LinkRuns::List)
+///   .break_whitespace(true);
+///
+/// assert_eq!(
+///   options.to_html(&Ast::from_string("# Hi\n\n=> /a A\n=> /b B")),
+///   "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta \
+///    charset=\"utf-8\">\n<title>Hi</title>\n<link rel=\"stylesheet\" \
+///    href=\"/style.css\">\n</head>\n<body>\n<h1 \
+///    id=\"hi\">Hi</h1><br><ul><li><a class=\"link\" \
+///    href=\"/a\">A</a></li><li><a class=\"link\" \
+///    href=\"/b\">B</a></li></ul>\n</body>\n</html>\n"
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[allow(clippy::module_name_repetitions, clippy::struct_excessive_bools)]
+pub struct HtmlOptions {
+  document:         bool,
+  lang:             Option<String>,
+  title:            Option<String>,
+  stylesheet:       Option<String>,
+  css:              Option<String>,
+  classes:          [Option<String>; 7],
+  links:            LinkRuns,
+  break_whitespace: bool,
+  heading_ids:      bool,
+  trusted:          bool,
+}
+
+impl Default for HtmlOptions {
+  fn default() -> Self { Self::new() }
+}
+
+impl HtmlOptions {
+  /// Create new `HtmlOptions` which convert Gemtext into an escaped HTML
+  /// fragment, without classes, whose headings have ids
+  #[must_use]
+  pub const fn new() -> Self {
+    Self {
+      document:         false,
+      lang:             None,
+      title:            None,
+      stylesheet:       None,
+      css:              None,
+      classes:          [None, None, None, None, None, None, None],
+      links:            LinkRuns::Breaks,
+      break_whitespace: false,
+      heading_ids:      true,
+      trusted:          false,
+    }
+  }
+
+  /// Whether to emit a full HTML document, with a doctype and a `<head>`,
+  /// rather than a fragment
+  ///
+  /// The `<title>` of the document is the text of its first heading, unless
+  /// it is set by [`HtmlOptions::title`].
+  #[must_use]
+  pub const fn document(mut self, document: bool) -> Self {
+    self.document = document;
+
+    self
+  }
+
+  /// Set the `lang` attribute of the `<html>` element of a full document
+  #[must_use]
+  pub fn lang(mut self, lang: impl Into<String>) -> Self {
+    self.lang = Some(lang.into());
+
+    self
+  }
+
+  /// Set the `<title>` of a full document, rather than taking it from the
+  /// first heading
+  #[must_use]
+  pub fn title(mut self, title: impl Into<String>) -> Self {
+    self.title = Some(title.into());
+
+    self
+  }
+
+  /// Link a full document to the stylesheet at `href`
+  #[must_use]
+  pub fn stylesheet(mut self, href: impl Into<String>) -> Self {
+    self.stylesheet = Some(href.into());
+
+    self
+  }
+
+  /// Embed `css` in a `<style>` element of a full document
+  #[must_use]
+  pub fn css(mut self, css: impl Into<String>) -> Self {
+    self.css = Some(css.into());
+
+    self
+  }
+
+  /// Set the class of the elements of a [`NodeKind`]
+  #[must_use]
+  pub fn class(mut self, kind: NodeKind, class: impl Into<String>) -> Self {
+    self.classes[kind.index()] = Some(class.into());
+
+    self
+  }
+
+  /// Set how runs of consecutive link lines are wrapped
+  #[must_use]
+  pub const fn links(mut self, links: LinkRuns) -> Self {
+    self.links = links;
+
+    self
+  }
+
+  /// Whether to emit a `<br>` for each whitespace line, rather than dropping
+  /// them
+  #[must_use]
+  pub const fn break_whitespace(mut self, break_whitespace: bool) -> Self {
+    self.break_whitespace = break_whitespace;
+
+    self
+  }
+
+  /// Whether to give each heading the unique [`slug`](fn@crate::ast::slug) of
+  /// its text as its `id`, so that it can be linked to
+  #[must_use]
+  pub const fn heading_ids(mut self, heading_ids: bool) -> Self {
+    self.heading_ids = heading_ids;
+
+    self
+  }
+
+  /// Whether the Gemtext is trusted to contain markup, in which case it is
+  /// neither escaped nor are its links neutralised, just like
+  /// [`Target::TrustedHTML`](super::Target::TrustedHTML)
+  #[must_use]
+  pub const fn trusted(mut self, trusted: bool) -> Self {
+    self.trusted = trusted;
+
+    self
+  }
+
+  /// The class of the elements of a [`NodeKind`], if any
+  #[must_use]
+  pub fn class_name(&self, kind: NodeKind) -> Option<&str> {
+    self.classes[kind.index()].as_deref()
+  }
+
+  /// Convert AST'd Gemtext into HTML
+  ///
+  /// Both owned and [`borrowed`] AST trees can be converted.
+  #[must_use]
+  pub fn to_html<'a>(&self, source: impl Into<borrowed::Ast<'a>>) -> String {
+    convert(source.into(), self)
+  }
+}
+
+/// Whether following a link to `to` would run code, e.g., `javascript:`
+fn is_dangerous(to: &str) -> bool {
+  // Browsers ignore leading whitespace and control characters, and tabs and
+  // newlines anywhere, when reading the scheme of a URL.
+  let scheme = to
+    .trim_start_matches(|character: char| character <= ' ')
+    .chars()
+    .filter(|character| !matches!(character, '\t' | '\n' | '\r'))
+    .take(
+      DANGEROUS_SCHEMES.iter().map(|scheme| scheme.len()).max().unwrap_or(0),
+    )
+    .collect::<String>()
+    .to_ascii_lowercase();
+
+  DANGEROUS_SCHEMES.iter().any(|dangerous| scheme.starts_with(dangerous))
+}
+
+pub struct Html<'a, W> {
+  output:       Sink<W>,
+  options:      &'a HtmlOptions,
+  links:        bool,
+  slugger:      Slugger,
+  /// The body of a full document, which is held back until the `<title>` of
+  /// the document is known from its first heading
+  pending:      Option<String>,
+  preformatted: &'a dyn Fn(&str, &str) -> Option<String>,
+}
+
+impl<'a, W: fmt::Write> Html<'a, W> {
+  pub fn new(
+    writer: W,
+    options: &'a HtmlOptions,
+    preformatted: &'a dyn Fn(&str, &str) -> Option<String>,
+  ) -> Self {
+    Self {
+      output: Sink::new(writer),
+      options,
+      links: false,
+      slugger: Slugger::new(),
+      pending: None,
+      preformatted,
+    }
+  }
+
+  pub const fn finish(&self) -> fmt::Result { self.output.finish() }
+
+  fn text<'t>(&self, text: &'t str) -> Cow<'t, str> {
+    if self.options.trusted { Cow::Borrowed(text) } else { escape(text) }
+  }
+
+  /// The opening tag of an element, with the class of its [`NodeKind`]
+  fn open(&self, tag: &str, kind: NodeKind) -> String {
+    self.options.class_name(kind).map_or_else(
+      || format!("<{tag}>"),
+      |class| format!("<{tag} class=\"{}\">", escape(class)),
+    )
+  }
+
+  /// Write to the body of the document, or hold it back if the head of the
+  /// document has not been written yet
+  fn write(&mut self, html: &str) {
+    match &mut self.pending {
+      Some(pending) => pending.push_str(html),
+      None => self.output.write_str(html),
+    }
+  }
+
+  /// Close the list of the current run of link lines, if any
+  fn close_links(&mut self) {
+    if self.links {
+      self.write("</ul>");
+
+      self.links = false;
+    }
+  }
+
+  /// Write the HTML of a node which is not a link line, ending the current
+  /// run of link lines
+  fn push(&mut self, html: &str) {
+    self.close_links();
+    self.write(html);
+  }
+
+  /// Write the head of a full document, and the body which was held back
+  /// until it
+  fn head(&mut self, title: Option<&str>) {
+    let pending = self.pending.take();
+
+    self.output.write_str("<!DOCTYPE html>\n");
+
+    match &self.options.lang {
+      Some(lang) => writeln!(self.output, "<html lang=\"{}\">", escape(lang)),
+      None => self.output.write_str("<html>\n"),
+    }
+
+    self.output.write_str("<head>\n<meta charset=\"utf-8\">\n");
+
+    if let Some(title) = title {
+      writeln!(self.output, "<title>{}</title>", self.text(title));
+    }
+
+    if let Some(href) = &self.options.stylesheet {
+      writeln!(
+        self.output,
+        "<link rel=\"stylesheet\" href=\"{}\">",
+        escape(href)
+      );
+    }
+
+    if let Some(css) = &self.options.css {
+      writeln!(self.output, "<style>{css}</style>");
+    }
+
+    self.output.write_str("</head>\n<body>\n");
+    self.output.write_str(&pending.unwrap_or_default());
+  }
+}
+
+impl<W: fmt::Write> Renderer for Html<'_, W> {
+  fn start_document(&mut self) {
+    if self.options.document {
+      match self.options.title.clone() {
+        Some(title) => self.head(Some(&title)),
+        None => self.pending = Some(String::new()),
       }
-      Node::List(items) => html.push_str(&format!(
-        "<ul>{}</ul>",
-        items
-          .iter()
-          .map(|i| format!("<li>{i}</li>"))
-          .collect::<Vec<String>>()
-          .join("\n")
-      )),
-      Node::Blockquote(text) =>
-        html.push_str(&format!("<blockquote>{text}</blockquote>")),
-      Node::PreformattedText { text, .. } => {
-        html.push_str(&format!("<pre>{text}</pre>"));
+    }
+  }
+
+  fn render_text(&mut self, text: &str) {
+    self.push(&format!(
+      "{}{}</p>",
+      self.open("p", NodeKind::Text),
+      self.text(text)
+    ));
+  }
+
+  fn render_link(&mut self, to: &str, text: Option<&str>) {
+    let text = self.text(text.unwrap_or(to));
+    let open = self.open("a", NodeKind::Link);
+    // Links with dangerous schemes are neutralised by dropping their
+    // location, unless the Gemtext is trusted.
+    let anchor = if !self.options.trusted && is_dangerous(to) {
+      format!("{open}{text}</a>")
+    } else {
+      format!(
+        "{} href=\"{}\">{text}</a>",
+        open.trim_end_matches('>'),
+        self.text(to)
+      )
+    };
+
+    match self.options.links {
+      LinkRuns::Breaks => self.write(&format!("{anchor}<br>")),
+      LinkRuns::List => {
+        if !self.links {
+          self.write("<ul>");
+
+          self.links = true;
+        }
+
+        self.write(&format!("<li>{anchor}</li>"));
       }
-      Node::Whitespace => {}
     }
   }
 
-  html
+  fn render_heading(&mut self, level: usize, text: &str) {
+    let tag = match level {
+      1 => "h1",
+      2 => "h2",
+      3 => "h3",
+      _ => "p",
+    };
+    let mut open = self.open(tag, NodeKind::Heading);
+
+    if self.options.heading_ids {
+      open = format!(
+        "{} id=\"{}\">",
+        open.trim_end_matches('>'),
+        escape(&self.slugger.slug(text))
+      );
+    }
+
+    // The first heading of a full document is its title.
+    if self.pending.is_some() {
+      self.close_links();
+      self.head(Some(text));
+    }
+
+    self.push(&format!("{open}{}</{tag}>", self.text(text)));
+  }
+
+  fn render_list(&mut self, items: &[Cow<'_, str>]) {
+    self.push(&format!(
+      "{}{}</ul>",
+      self.open("ul", NodeKind::List),
+      items
+        .iter()
+        .map(|i| format!("<li>{}</li>", self.text(i)))
+        .collect::<Vec<String>>()
+        .join("\n")
+    ));
+  }
+
+  fn render_blockquote(&mut self, text: &str) {
+    self.push(&format!(
+      "{}{}</blockquote>",
+      self.open("blockquote", NodeKind::Blockquote),
+      self.text(text)
+    ));
+  }
+
+  fn render_preformatted_text(&mut self, alt_text: Option<&str>, text: &str) {
+    let content = alt_text
+      .and_then(|alt_text| (self.preformatted)(alt_text, text))
+      .map_or_else(|| self.text(text), Cow::Owned);
+
+    self.push(&format!(
+      "{}{content}</pre>",
+      self.open("pre", NodeKind::PreformattedText),
+    ));
+  }
+
+  fn render_whitespace(&mut self) {
+    self.close_links();
+
+    if self.options.break_whitespace {
+      self.write(&self.open("br", NodeKind::Whitespace));
+    }
+  }
+
+  fn end_document(&mut self) {
+    self.close_links();
+
+    if self.options.document {
+      if self.pending.is_some() {
+        self.head(None);
+      }
+
+      self.output.write_str("\n</body>\n</html>\n");
+    }
+  }
 }
@@ -0,0 +1,64 @@
+// This file is part of Germ <https://github.com/gemrest/germ>.
+// Copyright (C) 2022-2022 Fuwn <contact@fuwn.me>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+//
+// Copyright (C) 2022-2022 Fuwn <contact@fuwn.me>
+// SPDX-License-Identifier: GPL-3.0-only
+
+use {crate::ast::Node, std::fmt::Write};
+
+/// Characters which mark a Gemtext line as something other than text, or which
+/// close a preformatted text block, when they start it
+const LINE_TYPES: [char; 5] = ['=', '*', '#', '>', '`'];
+
+/// Keep a node converted from another markup language from being read back as
+/// Gemtext of another kind
+///
+/// Text lines which start with a line type marker, e.g., `#`, and lines of
+/// preformatted text which start with a backtick, and so would close their
+/// block, are led by a space. Whitespace in link locations is percent-encoded.
+pub fn literal(node: Node) -> Node {
+  match node {
+    Node::Text(text) if text.starts_with(LINE_TYPES) =>
+      Node::Text(format!(" {text}")),
+    Node::Link { to, text } => Node::Link {
+      to: to.chars().fold(String::new(), |mut to, character| {
+        if character.is_whitespace() {
+          for byte in character.encode_utf8(&mut [0; 4]).bytes() {
+            let _ = write!(to, "%{byte:02X}");
+          }
+        } else {
+          to.push(character);
+        }
+
+        to
+      }),
+      text,
+    },
+    Node::PreformattedText { alt_text, text } => Node::PreformattedText {
+      alt_text,
+      text: text
+        .split_inclusive('\n')
+        .map(|line| {
+          if line.starts_with('`') {
+            format!(" {line}")
+          } else {
+            line.to_string()
+          }
+        })
+        .collect(),
+    },
+    node => node,
+  }
+}
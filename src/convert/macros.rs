@@ -67,3 +67,29 @@ macro_rules! gemini_to_md {
     )
   };
 }
+
+/// Convert Gemtext into plain text, with inline links
+///
+/// # Examples
+///
+/// ```rust
+/// assert_eq!(
+///   // Using a value
+///   germ::gemini_to_text!("=> /to hello !"),
+///   "hello ! </to>\n",
+/// );
+#[macro_export]
+macro_rules! gemini_to_text {
+  ($gemini:expr) => {
+    $crate::convert::from_ast(
+      &$crate::gemini_to_ast!($gemini),
+      &$crate::convert::Target::PlainText($crate::convert::LinkStyle::Inline),
+    )
+  };
+  ($($gemini:tt)*) => {
+    $crate::convert::from_ast(
+      &$crate::gemini_to_ast!{ $($gemini)* },
+      &$crate::convert::Target::PlainText($crate::convert::LinkStyle::Inline),
+    )
+  };
+}
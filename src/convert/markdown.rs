@@ -16,51 +16,73 @@
 // Copyright (C) 2022-2022 Fuwn <contact@fuwn.me>
 // SPDX-License-Identifier: GPL-3.0-only
 
-use crate::ast::Node;
+use {
+  super::{sink::Sink, Renderer},
+  crate::{ast::Slugger, escape::escape},
+  std::{borrow::Cow, fmt},
+};
 
-pub fn convert(source: &[Node]) -> String {
-  let mut markdown = String::new();
+pub struct Markdown<W> {
+  output:  Sink<W>,
+  slugger: Slugger,
+}
 
-  // Since we have an AST tree of the Gemtext, it is very easy to convert from
-  // this AST tree to an alternative markup format.
-  for node in source {
-    match node {
-      Node::Text(text) => markdown.push_str(&format!("{text}\n")),
-      Node::Link { to, text } => markdown.push_str(&text.clone().map_or_else(
-        || format!("<{to}>\n"),
-        |text| format!("[{text}]({to})\n"),
-      )),
-      Node::Heading { level, text } => {
-        markdown.push_str(&format!(
-          "{} {}\n",
-          match level {
-            1 => "#",
-            2 => "##",
-            3 => "###",
-            _ => "",
-          },
-          text
-        ));
-      }
-      Node::List(items) => markdown.push_str(&format!(
-        "{}\n",
-        items
-          .iter()
-          .map(|i| format!("- {i}"))
-          .collect::<Vec<String>>()
-          .join("\n"),
-      )),
-      Node::Blockquote(text) => markdown.push_str(&format!("> {text}\n")),
-      Node::PreformattedText { alt_text, text } => {
-        markdown.push_str(&format!(
-          "```{}\n{}```\n",
-          alt_text.clone().unwrap_or_default(),
-          text
-        ));
+impl<W: fmt::Write> Markdown<W> {
+  pub fn new(writer: W) -> Self {
+    Self { output: Sink::new(writer), slugger: Slugger::new() }
+  }
+
+  pub const fn finish(&self) -> fmt::Result { self.output.finish() }
+}
+
+impl<W: fmt::Write> Renderer for Markdown<W> {
+  fn render_text(&mut self, text: &str) {
+    writeln!(self.output, "{text}");
+  }
+
+  fn render_link(&mut self, to: &str, text: Option<&str>) {
+    match text {
+      Some(text) => writeln!(self.output, "[{text}]({to})"),
+      None => writeln!(self.output, "<{to}>"),
+    }
+  }
+
+  fn render_heading(&mut self, level: usize, text: &str) {
+    // Headings are anchored by the same ids as in HTML, rather than by
+    // whichever anchors a Markdown renderer would give them.
+    writeln!(
+      self.output,
+      "{} <a id=\"{}\"></a>{}",
+      match level {
+        1 => "#",
+        2 => "##",
+        3 => "###",
+        _ => "",
+      },
+      escape(&self.slugger.slug(text)),
+      text
+    );
+  }
+
+  fn render_list(&mut self, items: &[Cow<'_, str>]) {
+    for (index, item) in items.iter().enumerate() {
+      if index > 0 {
+        self.output.write_char('\n');
       }
-      Node::Whitespace => markdown.push('\n'),
+
+      write!(self.output, "- {item}");
     }
+
+    self.output.write_char('\n');
+  }
+
+  fn render_blockquote(&mut self, text: &str) {
+    writeln!(self.output, "> {text}");
+  }
+
+  fn render_preformatted_text(&mut self, alt_text: Option<&str>, text: &str) {
+    write!(self.output, "```{}\n{}```\n", alt_text.unwrap_or_default(), text);
   }
 
-  markdown
+  fn render_whitespace(&mut self) { self.output.write_char('\n'); }
 }
@@ -0,0 +1,90 @@
+// This file is part of Germ <https://github.com/gemrest/germ>.
+// Copyright (C) 2022-2022 Fuwn <contact@fuwn.me>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+//
+// Copyright (C) 2022-2022 Fuwn <contact@fuwn.me>
+// SPDX-License-Identifier: GPL-3.0-only
+
+use {
+  super::{sink::Sink, LinkStyle, Renderer},
+  std::{borrow::Cow, fmt},
+};
+
+pub struct PlainText<W> {
+  text:       Sink<W>,
+  links:      LinkStyle,
+  references: Vec<String>,
+}
+
+impl<W: fmt::Write> PlainText<W> {
+  pub const fn new(writer: W, links: LinkStyle) -> Self {
+    Self { text: Sink::new(writer), links, references: vec![] }
+  }
+
+  pub const fn finish(&self) -> fmt::Result { self.text.finish() }
+}
+
+impl<W: fmt::Write> Renderer for PlainText<W> {
+  fn render_text(&mut self, text: &str) {
+    writeln!(self.text, "{text}");
+  }
+
+  fn render_link(&mut self, to: &str, text: Option<&str>) {
+    let Some(text) = text else {
+      writeln!(self.text, "{to}");
+
+      return;
+    };
+
+    match self.links {
+      LinkStyle::Inline => writeln!(self.text, "{text} <{to}>"),
+      LinkStyle::References => {
+        self.references.push(to.to_string());
+        writeln!(self.text, "{text} [{}]", self.references.len());
+      }
+    }
+  }
+
+  fn render_heading(&mut self, _level: usize, text: &str) {
+    writeln!(self.text, "{text}");
+  }
+
+  fn render_list(&mut self, items: &[Cow<'_, str>]) {
+    for item in items {
+      writeln!(self.text, "• {item}");
+    }
+  }
+
+  fn render_blockquote(&mut self, text: &str) {
+    writeln!(self.text, "    {text}");
+  }
+
+  fn render_preformatted_text(&mut self, _alt_text: Option<&str>, text: &str) {
+    self.text.write_str(text);
+  }
+
+  fn render_whitespace(&mut self) { self.text.write_char('\n'); }
+
+  fn end_document(&mut self) {
+    // Referenced links are listed after the text, numbered in the order they
+    // were referenced.
+    if !self.references.is_empty() {
+      self.text.write_char('\n');
+
+      for (index, to) in self.references.iter().enumerate() {
+        writeln!(self.text, "[{}] {to}", index + 1);
+      }
+    }
+  }
+}
@@ -0,0 +1,112 @@
+// This file is part of Germ <https://github.com/gemrest/germ>.
+// Copyright (C) 2022-2022 Fuwn <contact@fuwn.me>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+//
+// Copyright (C) 2022-2022 Fuwn <contact@fuwn.me>
+// SPDX-License-Identifier: GPL-3.0-only
+
+use {crate::ast::Visit, std::borrow::Cow};
+
+/// Render AST'd Gemtext into an output format, one hook per node type
+///
+/// Every hook does nothing by default, so a renderer only has to implement
+/// the hooks of the node types it outputs. Each [`Target`] is converted by a
+/// built-in renderer.
+///
+/// # Example
+///
+/// ```rust
+/// use germ::convert::{render_with, Renderer};
+///
+/// #[derive(Default)]
+/// struct Shout(String);
+///
+/// impl Renderer for Shout {
+///   fn start_document(&mut self) { self.0.push_str("BEGIN\n"); }
+///
+///   fn render_text(&mut self, text: &str) {
+///     self.0.push_str(&format!("{}\n", text.to_uppercase()));
+///   }
+///
+///   fn render_link(&mut self, to: &str, _text: Option<&str>) {
+///     self.0.push_str(&format!("LINK {to}\n"));
+///   }
+///
+///   fn end_document(&mut self) { self.0.push_str("END\n"); }
+/// }
+///
+/// let mut shout = Shout::default();
+///
+/// render_with(&germ::ast::Ast::from_string("hi\n=> /a A"), &mut shout);
+///
+/// assert_eq!(shout.0, "BEGIN\nHI\nLINK /a\nEND\n");
+/// ```
+///
+/// [`Target`]: super::Target
+pub trait Renderer {
+  /// Render anything which comes before the nodes of the document
+  fn start_document(&mut self) {}
+
+  /// Render a text line
+  fn render_text(&mut self, _text: &str) {}
+
+  /// Render a link line
+  fn render_link(&mut self, _to: &str, _text: Option<&str>) {}
+
+  /// Render a heading line
+  fn render_heading(&mut self, _level: usize, _text: &str) {}
+
+  /// Render a collection of sequential list item lines
+  fn render_list(&mut self, _items: &[Cow<'_, str>]) {}
+
+  /// Render a blockquote line
+  fn render_blockquote(&mut self, _text: &str) {}
+
+  /// Render a preformatted block
+  fn render_preformatted_text(&mut self, _alt_text: Option<&str>, _text: &str) {
+  }
+
+  /// Render a whitespace line
+  fn render_whitespace(&mut self) {}
+
+  /// Render anything which comes after the nodes of the document
+  fn end_document(&mut self) {}
+}
+
+/// Dispatches each visited node to the hook of its node type
+pub struct Render<'a, R: ?Sized>(pub &'a mut R);
+
+impl<R: Renderer + ?Sized> Visit for Render<'_, R> {
+  fn visit_text(&mut self, text: &str) { self.0.render_text(text); }
+
+  fn visit_link(&mut self, to: &str, text: Option<&str>) {
+    self.0.render_link(to, text);
+  }
+
+  fn visit_heading(&mut self, level: usize, text: &str) {
+    self.0.render_heading(level, text);
+  }
+
+  fn visit_list(&mut self, items: &[Cow<'_, str>]) {
+    self.0.render_list(items);
+  }
+
+  fn visit_blockquote(&mut self, text: &str) { self.0.render_blockquote(text); }
+
+  fn visit_preformatted_text(&mut self, alt_text: Option<&str>, text: &str) {
+    self.0.render_preformatted_text(alt_text, text);
+  }
+
+  fn visit_whitespace(&mut self) { self.0.render_whitespace(); }
+}
@@ -0,0 +1,91 @@
+// This file is part of Germ <https://github.com/gemrest/germ>.
+// Copyright (C) 2022-2022 Fuwn <contact@fuwn.me>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+//
+// Copyright (C) 2022-2022 Fuwn <contact@fuwn.me>
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::{
+  fmt,
+  io::{self, Write as _},
+};
+
+/// Where a built-in renderer writes its output to
+///
+/// The hooks of a [`Renderer`](super::Renderer) can't fail, so the first
+/// error of the writer is kept, and everything written after it is dropped.
+pub struct Sink<W> {
+  writer: W,
+  result: fmt::Result,
+}
+
+impl<W: fmt::Write> Sink<W> {
+  pub const fn new(writer: W) -> Self { Self { writer, result: Ok(()) } }
+
+  pub fn write_str(&mut self, text: &str) {
+    if self.result.is_ok() {
+      self.result = self.writer.write_str(text);
+    }
+  }
+
+  pub fn write_char(&mut self, character: char) {
+    if self.result.is_ok() {
+      self.result = self.writer.write_char(character);
+    }
+  }
+
+  /// Allows `write!` into a `Sink`
+  pub fn write_fmt(&mut self, arguments: fmt::Arguments<'_>) {
+    if self.result.is_ok() {
+      self.result = self.writer.write_fmt(arguments);
+    }
+  }
+
+  /// The first error of the writer, if any
+  pub const fn finish(&self) -> fmt::Result { self.result }
+}
+
+/// Writes formatted text into an [`io::Write`] through a buffer, keeping its
+/// first error
+///
+/// Renderers write out many small pieces, so they are buffered rather than
+/// each being written to the writer on its own.
+pub struct IoWriter<W: io::Write> {
+  writer: io::BufWriter<W>,
+  error:  Option<io::Error>,
+}
+
+impl<W: io::Write> IoWriter<W> {
+  pub fn new(writer: W) -> Self {
+    Self { writer: io::BufWriter::new(writer), error: None }
+  }
+
+  /// Write out everything which is still buffered
+  pub fn flush(&mut self) -> io::Result<()> { self.writer.flush() }
+
+  /// Turn a formatting error into the I/O error which caused it
+  pub fn error(&mut self) -> io::Error {
+    self.error.take().unwrap_or_else(|| io::Error::other("formatter error"))
+  }
+}
+
+impl<W: io::Write> fmt::Write for IoWriter<W> {
+  fn write_str(&mut self, text: &str) -> fmt::Result {
+    self.writer.write_all(text.as_bytes()).map_err(|error| {
+      self.error = Some(error);
+
+      fmt::Error
+    })
+  }
+}
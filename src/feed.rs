@@ -0,0 +1,329 @@
+// This file is part of Germ <https://github.com/gemrest/germ>.
+// Copyright (C) 2022-2022 Fuwn <contact@fuwn.me>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+//
+// Copyright (C) 2022-2022 Fuwn <contact@fuwn.me>
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Read and write Gemini subscription feeds, and write them as Atom feeds
+//!
+//! A Gemtext page is a feed when its link lines start with a date, as
+//! described by the
+//! [Gemini subscription companion
+//! specification](gemini://geminiprotocol.net/docs/companion/subscription.gmi).
+//!
+//! ```gemini
+//! # My Gemlog
+//!
+//! => /posts/second.gmi 2024-05-02 - Second post
+//! => /posts/first.gmi 2024-05-01 First post
+//! ```
+//!
+//! # Example
+//!
+//! ```rust
+//! let ast = germ::ast::Ast::from_string(
+//!   "# My Gemlog\n=> /posts/first.gmi 2024-05-01 - First post",
+//! );
+//! let feed = germ::feed::Feed::from_ast(
+//!   &ast,
+//!   &url::Url::parse("gemini://gem.rest/gemlog/").unwrap(),
+//! );
+//!
+//! assert_eq!(feed.title(), Some("My Gemlog"));
+//! assert_eq!(feed.entries()[0].title(), "First post");
+//! assert_eq!(
+//!   feed.entries()[0].url().as_str(),
+//!   "gemini://gem.rest/posts/first.gmi"
+//! );
+//! ```
+
+mod atom;
+
+pub use atom::to_atom;
+use {
+  crate::ast::{Ast, Node},
+  std::{fmt, str::FromStr},
+  url::Url,
+};
+
+/// A calendar date, as feed entries are dated
+///
+/// # Example
+///
+/// ```rust
+/// let date = "2024-05-01".parse::<germ::feed::Date>().unwrap();
+///
+/// assert_eq!(date.month(), 5);
+/// assert_eq!(date.to_string(), "2024-05-01");
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Date {
+  year:  u16,
+  month: u8,
+  day:   u8,
+}
+
+impl Date {
+  /// Create a new `Date`, or `None` if there is no such day
+  #[must_use]
+  pub const fn new(year: u16, month: u8, day: u8) -> Option<Self> {
+    let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+    let days = match month {
+      1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+      4 | 6 | 9 | 11 => 30,
+      2 if leap => 29,
+      2 => 28,
+      _ => return None,
+    };
+
+    if day == 0 || day > days { None } else { Some(Self { year, month, day }) }
+  }
+
+  /// The year of the `Date`
+  #[must_use]
+  pub const fn year(&self) -> u16 { self.year }
+
+  /// The month of the `Date`, from 1 to 12
+  #[must_use]
+  pub const fn month(&self) -> u8 { self.month }
+
+  /// The day of the month of the `Date`, from 1 to 31
+  #[must_use]
+  pub const fn day(&self) -> u8 { self.day }
+}
+
+impl FromStr for Date {
+  type Err = InvalidDate;
+
+  fn from_str(date: &str) -> Result<Self, Self::Err> {
+    let bytes = date.as_bytes();
+
+    if bytes.len() != 10
+      || bytes[4] != b'-'
+      || bytes[7] != b'-'
+      || !date
+        .char_indices()
+        .all(|(index, c)| index == 4 || index == 7 || c.is_ascii_digit())
+    {
+      return Err(InvalidDate);
+    }
+
+    Self::new(
+      date[0..4].parse().map_err(|_| InvalidDate)?,
+      date[5..7].parse().map_err(|_| InvalidDate)?,
+      date[8..10].parse().map_err(|_| InvalidDate)?,
+    )
+    .ok_or(InvalidDate)
+  }
+}
+
+impl fmt::Display for Date {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+  }
+}
+
+/// The error of parsing a [`Date`] which is not a valid `YYYY-MM-DD` date
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct InvalidDate;
+
+impl fmt::Display for InvalidDate {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str("date is not a valid `YYYY-MM-DD` date")
+  }
+}
+
+impl std::error::Error for InvalidDate {}
+
+/// A single post of a [`Feed`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+  url:   Url,
+  date:  Date,
+  title: String,
+}
+
+impl Entry {
+  /// Create a new `Entry`
+  #[must_use]
+  pub fn new(url: Url, date: Date, title: impl Into<String>) -> Self {
+    Self { url, date, title: title.into() }
+  }
+
+  /// Read an `Entry` out of a link line, resolving its location against
+  /// `base`, or `None` if the link line is not a feed entry
+  ///
+  /// # Example
+  ///
+  /// ```rust
+  /// use germ::{ast::Node, feed::Entry};
+  ///
+  /// let base = url::Url::parse("gemini://gem.rest/").unwrap();
+  /// let link = |text: &str| Node::Link {
+  ///   to:   "/post.gmi".to_string(),
+  ///   text: Some(text.to_string()),
+  /// };
+  ///
+  /// assert!(Entry::from_node(&link("2024-05-01 - Post"), &base).is_some());
+  /// assert!(Entry::from_node(&link("About me"), &base).is_none());
+  /// ```
+  #[must_use]
+  pub fn from_node(node: &Node, base: &Url) -> Option<Self> {
+    let Node::Link { to, text: Some(text) } = node else {
+      return None;
+    };
+    let date = text.get(..10)?.parse().ok()?;
+    let rest = text[10..].trim_start();
+    // The date may be separated from the title by a dash, as long as the dash
+    // is not a part of the title itself.
+    let title = rest
+      .strip_prefix('-')
+      .filter(|title| {
+        title.is_empty() || title.starts_with(char::is_whitespace)
+      })
+      .unwrap_or(rest)
+      .trim();
+
+    Some(Self::new(base.join(to).ok()?, date, title))
+  }
+
+  /// Write the `Entry` as a link line
+  ///
+  /// # Example
+  ///
+  /// ```rust
+  /// use germ::feed::{Date, Entry};
+  ///
+  /// let entry = Entry::new(
+  ///   url::Url::parse("gemini://gem.rest/post.gmi").unwrap(),
+  ///   Date::new(2024, 5, 1).unwrap(),
+  ///   "Post",
+  /// );
+  ///
+  /// assert_eq!(
+  ///   entry.to_node().to_gemtext(),
+  ///   "=> gemini://gem.rest/post.gmi 2024-05-01 - Post"
+  /// );
+  /// ```
+  #[must_use]
+  pub fn to_node(&self) -> Node {
+    Node::Link {
+      to:   self.url.to_string(),
+      text: Some(if self.title.is_empty() {
+        self.date.to_string()
+      } else {
+        format!("{} - {}", self.date, self.title)
+      }),
+    }
+  }
+
+  /// Where the post of the `Entry` is
+  #[must_use]
+  pub const fn url(&self) -> &Url { &self.url }
+
+  /// When the post of the `Entry` was published
+  #[must_use]
+  pub const fn date(&self) -> Date { self.date }
+
+  /// The title of the post of the `Entry`
+  #[must_use]
+  pub fn title(&self) -> &str { &self.title }
+}
+
+/// A Gemini subscription feed
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Feed {
+  title:   Option<String>,
+  entries: Vec<Entry>,
+}
+
+impl Feed {
+  /// Create a new `Feed` without entries
+  #[must_use]
+  pub fn new(title: impl Into<String>) -> Self {
+    Self { title: Some(title.into()), entries: vec![] }
+  }
+
+  /// Read a `Feed` out of an [`Ast`], resolving the locations of its entries
+  /// against `base`, the URL of the feed
+  ///
+  /// The title of the feed is the text of the first heading, and every link
+  /// line whose text starts with a `YYYY-MM-DD` date is an entry. Link lines
+  /// whose location can't be resolved are skipped.
+  #[must_use]
+  pub fn from_ast(ast: &Ast, base: &Url) -> Self {
+    Self {
+      title:   ast.iter().find_map(|node| match node {
+        Node::Heading { text, .. } => Some(text.clone()),
+        _ => None,
+      }),
+      entries: ast
+        .iter()
+        .filter_map(|node| Entry::from_node(node, base))
+        .collect(),
+    }
+  }
+
+  /// Add an entry to the end of the `Feed`
+  #[must_use]
+  pub fn entry(mut self, entry: Entry) -> Self {
+    self.entries.push(entry);
+
+    self
+  }
+
+  /// Write the `Feed` as a feed page
+  ///
+  /// # Example
+  ///
+  /// ```rust
+  /// use germ::feed::{Date, Entry, Feed};
+  ///
+  /// let feed = Feed::new("My Gemlog").entry(Entry::new(
+  ///   url::Url::parse("gemini://gem.rest/post.gmi").unwrap(),
+  ///   Date::new(2024, 5, 1).unwrap(),
+  ///   "Post",
+  /// ));
+  ///
+  /// assert_eq!(
+  ///   feed.to_ast().to_gemtext(),
+  ///   "# My Gemlog\n\n=> gemini://gem.rest/post.gmi 2024-05-01 - Post"
+  /// );
+  /// ```
+  #[must_use]
+  pub fn to_ast(&self) -> Ast {
+    let mut nodes = vec![];
+
+    if let Some(title) = &self.title {
+      nodes.push(Node::Heading { level: 1, text: title.clone() });
+      nodes.push(Node::Whitespace);
+    }
+
+    nodes.extend(self.entries.iter().map(Entry::to_node));
+
+    Ast::from_nodes(nodes)
+  }
+
+  /// The title of the `Feed`
+  #[must_use]
+  pub fn title(&self) -> Option<&str> { self.title.as_deref() }
+
+  /// The entries of the `Feed`, in the order they appear
+  #[must_use]
+  pub fn entries(&self) -> &[Entry] { &self.entries }
+
+  /// Obtain mutable access to the entries of the `Feed`
+  pub fn entries_mut(&mut self) -> &mut Vec<Entry> { &mut self.entries }
+}
@@ -0,0 +1,135 @@
+// This file is part of Germ <https://github.com/gemrest/germ>.
+// Copyright (C) 2022-2022 Fuwn <contact@fuwn.me>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+//
+// Copyright (C) 2022-2022 Fuwn <contact@fuwn.me>
+// SPDX-License-Identifier: GPL-3.0-only
+
+use {
+  super::{Date, Entry, Feed},
+  crate::{
+    ast::{Ast, Node},
+    convert::{self, Target},
+    escape::escape,
+  },
+  url::Url,
+};
+
+/// Write a gemlog as an Atom 1.0 feed
+///
+/// The entries of the feed are read out of the gemlog's `index` like
+/// [`Feed::from_ast`] does, resolving their locations against `base`, the URL
+/// of the index. The title of the feed is the first heading of the index, and
+/// its author is `author`, or else the first level-two heading, falling back to
+/// the title.
+///
+/// The feed was last updated at `updated`, or else at the date of its newest
+/// entry. Atom requires a feed to be dated, so give `updated` for a feed which
+/// may have no entries; otherwise, it is left undated.
+///
+/// The content of each entry is its post, as handed back by `post`, converted
+/// to HTML. Entries without a post are linked to, but have no content.
+///
+/// # Example
+///
+/// ```rust
+/// use germ::ast::Ast;
+///
+/// let index = Ast::from_string(
+///   "# My Gemlog\n## Fuwn\n=> /first.gmi 2024-05-01 - First post",
+/// );
+/// let atom = germ::feed::to_atom(
+///   &index,
+///   &url::Url::parse("gemini://gem.rest/").unwrap(),
+///   None,
+///   None,
+///   |entry| Some(Ast::from_string(format!("# {}\nHello!", entry.title()))),
+/// );
+///
+/// assert!(atom.contains("<title>My Gemlog</title>"));
+/// assert!(atom.contains("<author><name>Fuwn</name></author>"));
+/// assert!(atom.contains("<updated>2024-05-01T00:00:00Z</updated>"));
+/// assert!(atom.contains(
+///   "<content type=\"html\">&lt;h1 id=&quot;first-post&quot;&gt;First \
+///    post&lt;/h1&gt;&lt;p&gt;Hello!&lt;/p&gt;</content>"
+/// ));
+/// ```
+#[must_use]
+pub fn to_atom(
+  index: &Ast,
+  base: &Url,
+  author: Option<&str>,
+  updated: Option<Date>,
+  mut post: impl FnMut(&Entry) -> Option<Ast>,
+) -> String {
+  let feed = Feed::from_ast(index, base);
+  let title = feed.title().unwrap_or_default();
+  let author = author.unwrap_or_else(|| {
+    index
+      .iter()
+      .find_map(|node| match node {
+        Node::Heading { level: 2, text } => Some(text.as_str()),
+        _ => None,
+      })
+      .unwrap_or(title)
+  });
+  let mut atom = format!(
+    "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<feed \
+     xmlns=\"http://www.w3.org/2005/Atom\">\n  <id>{}</id>\n  \
+     <title>{}</title>\n",
+    escape(base.as_str()),
+    escape(title),
+  );
+
+  if let Some(updated) =
+    updated.or_else(|| feed.entries().iter().map(Entry::date).max())
+  {
+    atom.push_str(&format!("  <updated>{}</updated>\n", timestamp(updated)));
+  }
+
+  atom
+    .push_str(&format!("  <author><name>{}</name></author>\n", escape(author)));
+
+  atom.push_str(&format!(
+    "  <link href=\"{}\" rel=\"alternate\"/>\n",
+    escape(base.as_str())
+  ));
+
+  for entry in feed.entries() {
+    let url = escape(entry.url().as_str());
+
+    atom.push_str(&format!(
+      "  <entry>\n    <id>{url}</id>\n    <title>{}</title>\n    \
+       <updated>{}</updated>\n    <link href=\"{url}\" rel=\"alternate\"/>\n",
+      escape(entry.title()),
+      timestamp(entry.date()),
+    ));
+
+    if let Some(post) = post(entry) {
+      atom.push_str(&format!(
+        "    <content type=\"html\">{}</content>\n",
+        escape(&convert::from_ast(&post, &Target::HTML))
+      ));
+    }
+
+    atom.push_str("  </entry>\n");
+  }
+
+  atom.push_str("</feed>\n");
+
+  atom
+}
+
+/// Format a [`Date`] as an RFC 3339 timestamp at midnight UTC
+fn timestamp(date: Date) -> String { format!("{date}T00:00:00Z") }
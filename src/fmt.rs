@@ -0,0 +1,159 @@
+// This file is part of Germ <https://github.com/gemrest/germ>.
+// Copyright (C) 2022-2022 Fuwn <contact@fuwn.me>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+//
+// Copyright (C) 2022-2022 Fuwn <contact@fuwn.me>
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Format Gemtext into a canonical form
+//!
+//! Formatting normalises the spacing of line identifiers, strips trailing
+//! whitespace, collapses runs of blank lines into one, and puts exactly one
+//! blank line around headings and preformatted blocks. Lines are never
+//! re-wrapped, as Gemtext clients wrap long lines themselves, and the content
+//! of preformatted blocks is kept byte for byte.
+//!
+//! # Example
+//!
+//! ```rust
+//! let source = "#Title\n=>\t/about   About  me\n\n\n```\n  code  \n```\nText";
+//! let formatted = germ::fmt::format(source);
+//!
+//! assert_eq!(
+//!   formatted,
+//!   "# Title\n\n=> /about About me\n\n```\n  code  \n```\n\nText\n"
+//! );
+//! assert!(!germ::fmt::check(source));
+//! assert!(germ::fmt::check(&formatted));
+//! ```
+
+use crate::ast::{
+  borrowed,
+  cst::{Cst, Node},
+};
+
+/// A formatted line, or a blank line between formatted lines
+enum Line {
+  Blank,
+  Text(String),
+}
+
+/// Format Gemtext into its canonical form
+///
+/// Formatting is idempotent, so formatting formatted Gemtext changes nothing.
+/// Non-empty output always ends with a single line ending.
+#[must_use]
+pub fn format(source: &str) -> String {
+  let cst = Cst::from_value(source);
+  let mut lines = vec![];
+  let mut spaced = false;
+  let mut blank = false;
+
+  for node in cst.nodes() {
+    let Some(text) = format_node(node) else {
+      blank = true;
+
+      continue;
+    };
+    let spacious =
+      matches!(node, Node::Heading(_) | Node::PreformattedText { .. });
+
+    // Blank lines are kept, but never more than one in a row, and headings
+    // and preformatted blocks always get one, except at the very start of the
+    // document.
+    if !lines.is_empty() && (blank || spaced || spacious) {
+      lines.push(Line::Blank);
+    }
+
+    lines.push(Line::Text(text));
+
+    spaced = spacious;
+    blank = false;
+  }
+
+  let mut formatted = String::new();
+
+  for line in lines {
+    if let Line::Text(text) = line {
+      formatted.push_str(&text);
+    }
+
+    formatted.push('\n');
+  }
+
+  formatted
+}
+
+/// Whether Gemtext is already in its canonical form, for checking Gemtext
+/// without rewriting it
+///
+/// # Example
+///
+/// ```rust
+/// assert!(germ::fmt::check("# Title\n\nText\n"));
+/// assert!(!germ::fmt::check("# Title\nText\n"));
+/// ```
+#[must_use]
+pub fn check(source: &str) -> bool { format(source) == source }
+
+/// Format a single node without its final line ending, or `None` if the node
+/// is a blank line
+fn format_node(node: &Node<'_>) -> Option<String> {
+  if let Node::PreformattedText { open, body, .. } = node {
+    // The content of a preformatted block is written out exactly as it was
+    // read, line endings included.
+    let mut text = format!("```{}\n", open.text().trim_end());
+
+    for line in body {
+      text.push_str(&line.to_string());
+
+      if line.ending().is_none() {
+        text.push('\n');
+      }
+    }
+
+    text.push_str("```");
+
+    return Some(text);
+  }
+
+  let prefixed = |prefix: &str, text: &str| {
+    let text = text.trim_end();
+
+    if text.is_empty() {
+      prefix.to_string()
+    } else {
+      format!("{prefix} {text}")
+    }
+  };
+
+  match node.lower() {
+    borrowed::Node::Text(text) =>
+      Some(text.trim_end().to_string()).filter(|text| !text.is_empty()),
+    borrowed::Node::Link { to, text } =>
+      Some(prefixed(&format!("=> {to}"), text.as_deref().unwrap_or_default())),
+    borrowed::Node::Heading { level, text } =>
+      Some(prefixed(&"#".repeat(level), &text)),
+    borrowed::Node::List(items) => Some(
+      items
+        .iter()
+        .map(|item| prefixed("*", item))
+        .collect::<Vec<_>>()
+        .join("\n"),
+    ),
+    borrowed::Node::Blockquote(text) => Some(prefixed(">", &text)),
+    borrowed::Node::PreformattedText { .. } | borrowed::Node::Whitespace =>
+      None,
+  }
+}
@@ -31,6 +31,8 @@
 
 #[cfg(feature = "ast")] pub mod ast;
 
+#[cfg(feature = "ast")] mod escape;
+
 #[cfg(feature = "convert")] pub mod convert;
 
 #[cfg(feature = "request")] pub mod request;
@@ -39,6 +41,12 @@
 
 #[cfg(feature = "quick")] pub mod quick;
 
+#[cfg(feature = "lint")] pub mod lint;
+
+#[cfg(feature = "feed")] pub mod feed;
+
+#[cfg(feature = "fmt")] pub mod fmt;
+
 #[cfg(feature = "example-gemtext")]
 pub const EXAMPLE_GEMTEXT: &str = r"```This is alt-text
 Here goes the pre-formatted text.
@@ -0,0 +1,455 @@
+// This file is part of Germ <https://github.com/gemrest/germ>.
+// Copyright (C) 2022-2022 Fuwn <contact@fuwn.me>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+//
+// Copyright (C) 2022-2022 Fuwn <contact@fuwn.me>
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Check Gemtext for likely mistakes
+//!
+//! A [`Linter`] runs a set of [`Rule`]s over Gemtext and reports a
+//! [`Warning`] for each problem it finds. Every rule is enabled by default,
+//! and each one can be disabled on its own.
+//!
+//! # Example
+//!
+//! ```rust
+//! use germ::lint::{Linter, Rule};
+//!
+//! let warnings = Linter::new()
+//!   .disable(Rule::MissingAltText)
+//!   .lint("# Title\n### Skipped\n=>/no-space");
+//!
+//! assert_eq!(warnings[0].rule(), Rule::SkippedHeadingLevel);
+//! assert_eq!(
+//!   warnings[1].to_string(),
+//!   "3:1: broken-link-syntax: link line has no whitespace after `=>`"
+//! );
+//! ```
+
+use {
+  crate::ast::{Ast, Node, Position, Span},
+  std::fmt,
+};
+
+/// The checks a [`Linter`] can run
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Rule {
+  /// A link line without a location
+  ///
+  /// ```gemini
+  /// =>
+  /// ```
+  EmptyLink,
+  /// A relative link which resolves to somewhere outside of the capsule
+  ///
+  /// ```gemini
+  /// => ../../../etc/passwd
+  /// ```
+  EscapingLink,
+  /// A preformatted block without alt-text, which screen readers are left to
+  /// read out character by character
+  MissingAltText,
+  /// A heading more than one level deeper than the heading before it
+  ///
+  /// ```gemini
+  /// # Title
+  /// ### Skipped a level
+  /// ```
+  SkippedHeadingLevel,
+  /// A line which ends in whitespace
+  TrailingWhitespace,
+  /// A line which looks like a link line, but is not quite one
+  ///
+  /// ```gemini
+  /// =>gemini://gem.rest/ No whitespace after the line identifier
+  /// See => gemini://gem.rest/ Link lines have to start with `=>`
+  /// ```
+  BrokenLinkSyntax,
+  /// A line longer than [`Linter::max_line_length`]
+  LongLine,
+}
+
+impl Rule {
+  /// Every `Rule`, in the order they are documented
+  pub const ALL: [Self; 7] = [
+    Self::EmptyLink,
+    Self::EscapingLink,
+    Self::MissingAltText,
+    Self::SkippedHeadingLevel,
+    Self::TrailingWhitespace,
+    Self::BrokenLinkSyntax,
+    Self::LongLine,
+  ];
+
+  /// The kebab-case name of the `Rule`, as used in a [`Warning`]'s
+  /// [`Display`](fmt::Display)
+  ///
+  /// # Example
+  ///
+  /// ```rust
+  /// assert_eq!(germ::lint::Rule::EmptyLink.name(), "empty-link");
+  /// ```
+  #[must_use]
+  pub const fn name(&self) -> &'static str {
+    match self {
+      Self::EmptyLink => "empty-link",
+      Self::EscapingLink => "escaping-link",
+      Self::MissingAltText => "missing-alt-text",
+      Self::SkippedHeadingLevel => "skipped-heading-level",
+      Self::TrailingWhitespace => "trailing-whitespace",
+      Self::BrokenLinkSyntax => "broken-link-syntax",
+      Self::LongLine => "long-line",
+    }
+  }
+
+  /// Whether the `Rule` needs the Gemtext source, rather than just an
+  /// [`Ast`], to be checked
+  const fn needs_source(self) -> bool {
+    matches!(self, Self::TrailingWhitespace | Self::LongLine)
+  }
+}
+
+impl fmt::Display for Rule {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str(self.name())
+  }
+}
+
+/// A problem found by a [`Linter`], and where it was found
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Warning {
+  rule:    Rule,
+  message: String,
+  span:    Option<Span>,
+}
+
+impl Warning {
+  fn new(rule: Rule, message: impl Into<String>, span: Option<Span>) -> Self {
+    Self { rule, message: message.into(), span }
+  }
+
+  /// The [`Rule`] which found the problem
+  #[must_use]
+  pub const fn rule(&self) -> Rule { self.rule }
+
+  /// A description of the problem
+  #[must_use]
+  pub fn message(&self) -> &str { &self.message }
+
+  /// Where in the source the problem is
+  ///
+  /// The location is only known for [`Ast`]s which were parsed from
+  /// Gemtext.
+  #[must_use]
+  pub const fn span(&self) -> Option<Span> { self.span }
+}
+
+impl fmt::Display for Warning {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    if let Some(span) = self.span {
+      write!(f, "{}:{}: ", span.start().line(), span.start().column())?;
+    }
+
+    write!(f, "{}: {}", self.rule, self.message)
+  }
+}
+
+/// Runs a configurable set of [`Rule`]s over Gemtext
+///
+/// # Example
+///
+/// ```rust
+/// use germ::lint::{Linter, Rule};
+///
+/// let linter =
+///   Linter::new().disable(Rule::LongLine).path("/gemlog/first-post.gmi");
+///
+/// assert!(linter.lint("=> ../index.gmi Back").is_empty());
+/// assert_eq!(
+///   linter.lint("=> ../../index.gmi Too far back")[0].rule(),
+///   Rule::EscapingLink
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Linter {
+  rules:           Vec<Rule>,
+  max_line_length: usize,
+  path:            String,
+}
+
+impl Default for Linter {
+  fn default() -> Self { Self::new() }
+}
+
+impl Linter {
+  /// Create a new `Linter` with every [`Rule`] enabled
+  #[must_use]
+  pub fn new() -> Self {
+    Self {
+      rules:           Rule::ALL.to_vec(),
+      max_line_length: 1024,
+      path:            "/".to_string(),
+    }
+  }
+
+  /// Enable a [`Rule`]
+  #[must_use]
+  pub fn enable(mut self, rule: Rule) -> Self {
+    if !self.rules.contains(&rule) {
+      self.rules.push(rule);
+    }
+
+    self
+  }
+
+  /// Disable a [`Rule`]
+  #[must_use]
+  pub fn disable(mut self, rule: Rule) -> Self {
+    self.rules.retain(|enabled| *enabled != rule);
+
+    self
+  }
+
+  /// Whether a [`Rule`] is enabled
+  #[must_use]
+  pub fn is_enabled(&self, rule: Rule) -> bool { self.rules.contains(&rule) }
+
+  /// Set the number of characters a line may have before
+  /// [`Rule::LongLine`] warns about it, which defaults to 1024
+  #[must_use]
+  pub const fn max_line_length(mut self, max_line_length: usize) -> Self {
+    self.max_line_length = max_line_length;
+
+    self
+  }
+
+  /// Set the path of the linted Gemtext within its capsule, which relative
+  /// links are resolved against for [`Rule::EscapingLink`], and defaults to
+  /// `/`
+  #[must_use]
+  pub fn path(mut self, path: impl Into<String>) -> Self {
+    self.path = path.into();
+
+    self
+  }
+
+  /// Lint Gemtext, in source order
+  ///
+  /// # Example
+  ///
+  /// ```rust
+  /// let warnings = germ::lint::Linter::new().lint("Hi \n=>");
+  ///
+  /// assert_eq!(warnings[0].rule(), germ::lint::Rule::TrailingWhitespace);
+  /// assert_eq!(
+  ///   warnings[1].to_string(),
+  ///   "2:1: empty-link: link line has no location"
+  /// );
+  /// ```
+  #[must_use]
+  pub fn lint(&self, source: &str) -> Vec<Warning> {
+    let (ast, _) = Ast::from_str_lenient(source);
+    let mut warnings = self.lint_nodes(&ast, Some(source));
+
+    self.lint_lines(source, &mut warnings);
+    warnings.sort_by_key(|warning| warning.span.map(|span| span.start()));
+
+    warnings
+  }
+
+  /// Lint an [`Ast`], in node order
+  ///
+  /// Only the rules which can be checked against nodes are run; the
+  /// whitespace and length of lines are only known to [`Linter::lint`].
+  #[must_use]
+  pub fn lint_ast(&self, ast: &Ast) -> Vec<Warning> {
+    self.lint_nodes(ast, None)
+  }
+
+  fn lint_nodes(&self, ast: &Ast, source: Option<&str>) -> Vec<Warning> {
+    let mut warnings = vec![];
+    let mut previous_level = None;
+
+    for (index, node) in ast.inner().iter().enumerate() {
+      let spans = ast.spans().get(index);
+      let span = spans.map(crate::ast::NodeSpan::span);
+      let mut warn = |rule: Rule, message: &str, at: Option<Span>| {
+        if self.is_enabled(rule) {
+          warnings.push(Warning::new(rule, message, at));
+        }
+      };
+
+      match node {
+        // Link lines without a location are parsed as text lines, so their
+        // line identifier is all that is left of them.
+        Node::Text(text)
+          if text
+            .strip_prefix("=>")
+            .map_or(false, |rest| rest.trim().is_empty()) =>
+          warn(Rule::EmptyLink, "link line has no location", span),
+        Node::Text(text) if text.contains("=>") => warn(
+          Rule::BrokenLinkSyntax,
+          "text line contains `=>`, which only starts a link line at the \
+           start of a line",
+          span,
+        ),
+        Node::Link { to, .. } => {
+          if to.trim().is_empty() {
+            warn(Rule::EmptyLink, "link line has no location", span);
+
+            continue;
+          }
+
+          if let Some(spans) = spans {
+            let marker = source.map(|source| &source[spans.span().range()]);
+
+            if marker.map_or(false, |line| !line.starts_with("=>")) {
+              warn(
+                Rule::BrokenLinkSyntax,
+                "link line does not start with `=>`",
+                span,
+              );
+            } else if spans.to().map_or(false, |to| {
+              to.start().offset() == spans.span().start().offset() + 2
+            }) {
+              warn(
+                Rule::BrokenLinkSyntax,
+                "link line has no whitespace after `=>`",
+                span,
+              );
+            }
+          }
+
+          if escapes(&self.path, to) {
+            warn(
+              Rule::EscapingLink,
+              "relative link resolves outside of the capsule",
+              spans.and_then(crate::ast::NodeSpan::to).or(span),
+            );
+          }
+        }
+        Node::Heading { level, .. } => {
+          if previous_level.map_or(false, |previous| *level > previous + 1) {
+            warn(
+              Rule::SkippedHeadingLevel,
+              &format!(
+                "heading skips from level {} to level {level}",
+                previous_level.unwrap_or_default()
+              ),
+              span,
+            );
+          }
+
+          previous_level = Some(*level);
+        }
+        Node::PreformattedText { alt_text, .. }
+          if alt_text.as_deref().map_or(true, |alt| alt.trim().is_empty()) =>
+          warn(Rule::MissingAltText, "preformatted block has no alt-text", span),
+        _ => {}
+      }
+    }
+
+    warnings
+  }
+
+  fn lint_lines(&self, source: &str, warnings: &mut Vec<Warning>) {
+    if !Rule::ALL
+      .iter()
+      .any(|rule| rule.needs_source() && self.is_enabled(*rule))
+    {
+      return;
+    }
+
+    let mut offset = 0;
+    let mut preformatted = false;
+
+    for (index, line) in source.split('\n').enumerate() {
+      let next = offset + line.len() + 1;
+      let line = line.strip_suffix('\r').unwrap_or(line);
+      let position = |at: usize| {
+        Position::new(index + 1, line[..at].chars().count() + 1, offset + at)
+      };
+
+      if line.starts_with('`') {
+        preformatted = !preformatted;
+      } else if !preformatted && self.is_enabled(Rule::TrailingWhitespace) {
+        let trimmed = line.trim_end().len();
+
+        if trimmed != line.len() {
+          warnings.push(Warning::new(
+            Rule::TrailingWhitespace,
+            "line has trailing whitespace",
+            Some(Span::new(position(trimmed), position(line.len()))),
+          ));
+        }
+      }
+
+      if self.is_enabled(Rule::LongLine)
+        && line.chars().count() > self.max_line_length
+      {
+        warnings.push(Warning::new(
+          Rule::LongLine,
+          format!("line is longer than {} characters", self.max_line_length),
+          Some(Span::new(position(0), position(line.len()))),
+        ));
+      }
+
+      offset = next;
+    }
+  }
+}
+
+/// Whether a link, relative to the Gemtext at `path`, leaves the root of the
+/// capsule
+fn escapes(path: &str, to: &str) -> bool {
+  // Links with a scheme or an authority are not relative to the capsule at
+  // all, so they can't escape it.
+  let scheme = to.split_once(':').map_or(false, |(scheme, _)| {
+    scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+      && scheme
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+  });
+
+  if scheme || to.starts_with("//") {
+    return false;
+  }
+
+  let to = to.split(['?', '#']).next().unwrap_or_default();
+  let mut directories = vec![];
+
+  if !to.starts_with('/') {
+    directories = path.split('/').filter(|s| !s.is_empty()).collect();
+
+    // The last segment of the path is the Gemtext itself, unless the path is
+    // a directory.
+    if !path.ends_with('/') {
+      directories.pop();
+    }
+  }
+
+  for segment in to.split('/') {
+    match segment {
+      "" | "." => {}
+      ".." =>
+        if directories.pop().is_none() {
+          return true;
+        },
+      segment => directories.push(segment),
+    }
+  }
+
+  false
+}
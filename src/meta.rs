@@ -20,14 +20,39 @@ use std::{borrow::Cow, collections::HashMap, fmt::Display};
 
 /// Structure-ize a Gemini response's meta section into it's mime type and it's
 /// parameters.
+///
+/// # Serialisation
+///
+/// With the `serde` feature, a `Meta` is (de)serialised as an object of its
+/// mime type and its parameters, which are sorted by name:
+///
+/// ```json
+/// {"mime":"text/gemini","parameters":{}}
+/// {"mime":"text/gemini","parameters":{"charset":"utf-8","lang":"en"}}
+/// ```
 #[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Meta {
   /// The mime type of a Gemini response
   mime:       String,
   /// The parameters of a Gemini response
+  #[cfg_attr(feature = "serde", serde(serialize_with = "sorted"))]
   parameters: HashMap<String, String>,
 }
 
+/// Serialise the parameters of a [`Meta`] in order of their names, so that
+/// the same `Meta` is always serialised the same way
+#[cfg(feature = "serde")]
+fn sorted<S: serde::Serializer>(
+  parameters: &HashMap<String, String>,
+  serializer: S,
+) -> Result<S::Ok, S::Error> {
+  let mut parameters = parameters.iter().collect::<Vec<_>>();
+
+  parameters.sort();
+  serializer.collect_map(parameters)
+}
+
 impl Display for Meta {
   fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
     write!(f, "{}{}", self.mime, {
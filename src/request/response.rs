@@ -76,3 +76,35 @@ impl Response {
   #[must_use]
   pub const fn suite(&self) -> &Option<SupportedCipherSuite> { &self.suite }
 }
+
+/// With the `serde` feature, a `Response` is serialised as an object of its
+/// status code, meta, content, size, and cipher suite, e.g.,
+/// `{"status":20,"meta":"text/gemini","content":"Hi\r\n","size":19,"suite":
+/// 4866}`.
+///
+/// The cipher suite is its IANA-assigned number, e.g., `4866` (`0x1302`) for
+/// `TLS_AES_256_GCM_SHA384`, which stays the same across versions of `rustls`.
+///
+/// A `Response` can't be deserialised, as its cipher suite can't be recovered
+/// from its number.
+#[cfg(feature = "serde")]
+impl serde::Serialize for Response {
+  fn serialize<S: serde::Serializer>(
+    &self,
+    serializer: S,
+  ) -> Result<S::Ok, S::Error> {
+    use serde::ser::SerializeStruct;
+
+    let mut response = serializer.serialize_struct("Response", 5)?;
+
+    response.serialize_field("status", &self.status)?;
+    response.serialize_field("meta", &self.meta)?;
+    response.serialize_field("content", &self.content)?;
+    response.serialize_field("size", &self.size)?;
+    response.serialize_field(
+      "suite",
+      &self.suite.map(|suite| suite.suite().get_u16()),
+    )?;
+    response.end()
+  }
+}
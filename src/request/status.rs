@@ -28,7 +28,15 @@ use std::{fmt, fmt::Formatter};
 /// assert_eq!(Status::from(10), Status::Input);
 /// assert_eq!(i32::from(Status::Input), 10);
 /// ```
+///
+/// With the `serde` feature, a `Status` is (de)serialised as its numeric code,
+/// e.g., `20`, rather than by name.
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(
+  feature = "serde",
+  derive(serde::Serialize, serde::Deserialize),
+  serde(into = "i32", from = "i32")
+)]
 pub enum Status {
   Input,
   SensitiveInput,
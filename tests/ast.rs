@@ -18,9 +18,12 @@
 
 #[cfg(test)]
 mod test {
-  use germ::{
-    ast::{Ast, Node},
-    EXAMPLE_GEMTEXT,
+  use {
+    germ::{
+      ast::{borrowed, Ast, DiagnosticKind, Node, Severity},
+      EXAMPLE_GEMTEXT,
+    },
+    std::borrow::Cow,
   };
 
   #[test]
@@ -84,4 +87,245 @@ mod test {
       "=> / Home"
     );
   }
+
+  #[test]
+  fn spans_of_single_line_nodes() {
+    let source = "# Heading\n=> /to  some text\n> quote";
+    let ast = Ast::from_string(source);
+    let spans = ast.spans();
+
+    assert_eq!(spans.len(), 3);
+    assert_eq!(&source[spans[0].span().range()], "# Heading");
+    assert_eq!(&source[spans[0].text().unwrap().range()], "Heading");
+    assert_eq!(&source[spans[1].to().unwrap().range()], "/to");
+    assert_eq!(&source[spans[1].text().unwrap().range()], "some text");
+    assert_eq!(spans[1].to().unwrap().start().line(), 2);
+    assert_eq!(spans[1].to().unwrap().start().column(), 4);
+    assert_eq!(&source[spans[2].text().unwrap().range()], "quote");
+  }
+
+  #[test]
+  fn spans_of_multi_line_nodes() {
+    let source = "* one\r\n* two\r\n```alt\r\nbody\r\n```\r\n";
+    let ast = Ast::from_string(source);
+    let spans = ast.spans();
+
+    assert_eq!(spans.len(), 3);
+    assert_eq!(&source[spans[0].span().range()], "* one\r\n* two");
+    assert_eq!(
+      spans[0]
+        .items()
+        .iter()
+        .map(|item| &source[item.range()])
+        .collect::<Vec<_>>(),
+      vec!["one", "two"]
+    );
+    assert_eq!(spans[1].span().start().line(), 3);
+    assert_eq!(spans[1].span().end().line(), 5);
+    assert_eq!(&source[spans[1].alt_text().unwrap().range()], "alt");
+    assert_eq!(&source[spans[1].text().unwrap().range()], "body\r\n");
+    assert_eq!(ast.inner()[2], Node::Whitespace);
+    assert_eq!(spans[2].span().start().offset(), source.len());
+  }
+
+  #[test]
+  fn spans_count_columns_in_characters() {
+    let ast = Ast::from_string("é\n=> /ü ünïcode");
+    let text = ast.spans()[1].text().unwrap();
+
+    assert_eq!(text.start().column(), 7);
+    assert_eq!(text.start().offset(), 10);
+  }
+
+  #[test]
+  fn spans_are_empty_for_ast_from_nodes() {
+    assert!(Ast::from_nodes(vec![Node::Whitespace]).spans().is_empty());
+  }
+
+  #[test]
+  fn spans_are_forgotten_when_nodes_are_borrowed_mutably() {
+    let source = "# Hi\n=> /to";
+    let mut ast = Ast::from_string(source);
+
+    assert_eq!(ast.iter_mut().count(), 2);
+    assert!(ast.spans().is_empty());
+
+    let mut ast = Ast::from_string(source);
+
+    ast[0] = Node::Text("Hi".to_string());
+    assert!(ast.spans().is_empty());
+
+    let mut ast = Ast::from_string(source);
+
+    assert_eq!((&mut ast).into_iter().count(), 2);
+    assert!(ast.spans().is_empty());
+    assert_eq!(Ast::from_string(source).spans().len(), 2);
+  }
+
+  #[test]
+  fn borrowed_ast_matches_owned_ast() {
+    let borrowed = borrowed::Ast::from_value(EXAMPLE_GEMTEXT);
+
+    assert_eq!(borrowed.to_gemtext(), EXAMPLE_GEMTEXT);
+    assert_eq!(borrowed.into_owned(), Ast::from_string(EXAMPLE_GEMTEXT));
+  }
+
+  #[test]
+  fn borrowed_ast_borrows_the_spans_of_owned_ast() {
+    let ast = Ast::from_string("# Hi\n=> /to");
+    let borrowed = borrowed::Ast::from(&ast);
+
+    assert_eq!(borrowed.spans(), ast.spans());
+    assert!(std::ptr::eq(borrowed.spans(), ast.spans()));
+  }
+
+  #[test]
+  fn borrowed_ast_slices_into_source() {
+    let ast = borrowed::Ast::from_value("=> /to some text\n```alt\nbody\n```");
+
+    assert!(matches!(&ast.inner()[0], borrowed::Node::Link {
+      to:   Cow::Borrowed("/to"),
+      text: Some(Cow::Borrowed("some text")),
+    }));
+    assert!(matches!(&ast.inner()[1], borrowed::Node::PreformattedText {
+      text: Cow::Borrowed("body\n"),
+      ..
+    }));
+  }
+
+  #[test]
+  fn borrowed_ast_copies_normalised_text() {
+    let ast =
+      borrowed::Ast::from_value("=> /to some  text\r\n```\r\nbody\r\n```");
+
+    assert!(matches!(
+      &ast.inner()[0],
+      borrowed::Node::Link { text: Some(Cow::Owned(text)), .. } if text == "some text"
+    ));
+    assert!(matches!(
+      &ast.inner()[1],
+      borrowed::Node::PreformattedText { text: Cow::Owned(text), .. } if text == "body\n"
+    ));
+  }
+
+  #[test]
+  fn empty_link_lines_are_recovered_as_text() {
+    let (ast, diagnostics) = Ast::from_str_lenient("=>\n=\n=>   \n=> /");
+
+    assert_eq!(ast.inner()[..3], [
+      Node::Text("=>".to_string()),
+      Node::Text("=".to_string()),
+      Node::Text("=>   ".to_string()),
+    ]);
+    assert_eq!(ast.inner()[3], Node::Link {
+      to:   "/".to_string(),
+      text: None,
+    });
+    assert_eq!(diagnostics.len(), 3);
+    assert_eq!(diagnostics[1].kind(), DiagnosticKind::EmptyLink);
+    assert_eq!(diagnostics[1].span().start().line(), 2);
+  }
+
+  #[test]
+  fn unterminated_preformatted_blocks_are_closed() {
+    let (ast, diagnostics) = Ast::from_str_lenient("# Hi\n```alt\nbody");
+
+    assert_eq!(ast.inner()[1], Node::PreformattedText {
+      alt_text: Some("alt".to_string()),
+      text:     "body\n".to_string(),
+    });
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].kind(), DiagnosticKind::UnterminatedPreformatted);
+    assert_eq!(diagnostics[0].span().start().line(), 2);
+    assert_eq!(diagnostics[0].span().end().line(), 3);
+  }
+
+  #[test]
+  fn deep_headings_are_warned_about() {
+    let (ast, diagnostics) = Ast::from_str_lenient("#### Deep");
+
+    assert_eq!(ast.inner()[0], Node::Heading {
+      level: 4,
+      text:  "Deep".to_string(),
+    });
+    assert_eq!(diagnostics[0].severity(), Severity::Warning);
+    assert_eq!(diagnostics[0].span().range(), 0..4);
+    assert!(Ast::try_from_str("#### Deep").is_ok());
+  }
+
+  #[test]
+  fn try_from_str_fails_on_errors() {
+    let errors = Ast::try_from_str("=> / Home\n=>\n```").unwrap_err();
+
+    assert_eq!(errors.diagnostics().len(), 2);
+    assert_eq!(
+      errors.to_string(),
+      "2:1: error: link line has no location\n3:1: error: preformatted block \
+       is never closed"
+    );
+    assert_eq!(
+      Ast::try_from_str(EXAMPLE_GEMTEXT),
+      Ok(Ast::from_string(EXAMPLE_GEMTEXT))
+    );
+  }
+
+  #[test]
+  fn edit_nodes_in_place() {
+    let mut ast = Ast::from_string("# Hi\n=> /a A\n\n> Quote");
+
+    ast.retain(|node| *node != Node::Whitespace);
+
+    assert_eq!(ast.spans()[2].span().start().line(), 4);
+
+    ast[0] = Node::Heading { level: 2, text: "Hello".to_string() };
+
+    let link = ast.remove(1);
+
+    ast.insert(0, link);
+    ast.push(Node::Text("End".to_string()));
+    ast.extend(vec![Node::Whitespace]);
+
+    assert!(ast.spans().is_empty());
+    assert_eq!(ast.len(), 5);
+    assert_eq!(ast.to_gemtext(), "=> /a A\n## Hello\n> Quote\nEnd\n\n");
+  }
+
+  #[test]
+  fn iterate_over_and_collect_nodes() {
+    let mut ast = Ast::from_string("* a\n\n* b");
+
+    for node in &mut ast {
+      if let Node::List(items) = node {
+        items.push("c".to_string());
+      }
+    }
+
+    let lists = ast
+      .into_iter()
+      .filter(|node| matches!(node, Node::List(_)))
+      .collect::<Ast>();
+
+    assert_eq!((&lists).into_iter().count(), 2);
+    assert_eq!(lists[1..], [Node::List(vec![
+      "b".to_string(),
+      "c".to_string()
+    ])]);
+  }
+
+  #[test]
+  fn replace_and_remove_sections() {
+    let mut ast = Ast::from_string("# A\n## B\nOld\n### C\nSub\n## D\nEnd");
+
+    assert_eq!(
+      ast.replace_section("B", vec![Node::Text("New".to_string())]),
+      Some(vec![
+        Node::Text("Old".to_string()),
+        Node::Heading { level: 3, text: "C".to_string() },
+        Node::Text("Sub".to_string()),
+      ])
+    );
+    assert_eq!(ast.replace_section("Missing", vec![]), None);
+    assert_eq!(ast.remove_section("D").map(|nodes| nodes.len()), Some(2));
+    assert_eq!(ast.to_gemtext(), "# A\n## B\nNew");
+  }
 }
@@ -0,0 +1,116 @@
+// This file is part of Germ <https://github.com/gemrest/germ>.
+// Copyright (C) 2022-2022 Fuwn <contact@fuwn.me>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+//
+// Copyright (C) 2022-2022 Fuwn <contact@fuwn.me>
+// SPDX-License-Identifier: GPL-3.0-only
+
+#[cfg(test)]
+mod test {
+  use germ::{
+    ast::Ast,
+    feed::{to_atom, Date},
+  };
+
+  #[test]
+  fn atom_feed_from_a_gemlog() {
+    let index = Ast::from_string(
+      "# Tom & Jerry's <Gemlog>\n=> /b.gmi 2024-05-03 - B\n=> /a.gmi \
+       2024-05-01 - A\n=> /about.gmi About",
+    );
+    let atom = to_atom(
+      &index,
+      &url::Url::parse("gemini://gem.rest/").unwrap(),
+      None,
+      None,
+      |entry| {
+        (entry.title() == "A")
+          .then(|| Ast::from_string("=> /x \"Quoted\" & <linked>"))
+      },
+    );
+
+    assert_eq!(
+      atom,
+      r#"<?xml version="1.0" encoding="utf-8"?>
+<feed xmlns="http://www.w3.org/2005/Atom">
+  <id>gemini://gem.rest/</id>
+  <title>Tom &amp; Jerry&#39;s &lt;Gemlog&gt;</title>
+  <updated>2024-05-03T00:00:00Z</updated>
+  <author><name>Tom &amp; Jerry&#39;s &lt;Gemlog&gt;</name></author>
+  <link href="gemini://gem.rest/" rel="alternate"/>
+  <entry>
+    <id>gemini://gem.rest/b.gmi</id>
+    <title>B</title>
+    <updated>2024-05-03T00:00:00Z</updated>
+    <link href="gemini://gem.rest/b.gmi" rel="alternate"/>
+  </entry>
+  <entry>
+    <id>gemini://gem.rest/a.gmi</id>
+    <title>A</title>
+    <updated>2024-05-01T00:00:00Z</updated>
+    <link href="gemini://gem.rest/a.gmi" rel="alternate"/>
+    <content type="html">&lt;a href=&quot;/x&quot;&gt;&amp;quot;Quoted&amp;quot; &amp;amp; &amp;lt;linked&amp;gt;&lt;/a&gt;&lt;br&gt;</content>
+  </entry>
+</feed>
+"#
+    );
+  }
+
+  #[test]
+  fn atom_feed_author_and_date_default_to_the_index() {
+    let atom = to_atom(
+      &Ast::from_string(
+        "# Gemlog\n## Fuwn\n## Not the author\n=> /a.gmi 2024-05-01 - A\n=> \
+         /b.gmi 2024-04-01 - B",
+      ),
+      &url::Url::parse("gemini://gem.rest/").unwrap(),
+      None,
+      None,
+      |_| None,
+    );
+
+    assert!(atom.contains(
+      "  <updated>2024-05-01T00:00:00Z</updated>\n  \
+       <author><name>Fuwn</name></author>\n"
+    ));
+  }
+
+  #[test]
+  fn atom_feed_author_and_date_can_be_given() {
+    let index = Ast::from_string("# Empty\n## Not the author");
+    let base = url::Url::parse("gemini://gem.rest/").unwrap();
+
+    assert_eq!(
+      to_atom(
+        &index,
+        &base,
+        Some("Tom & Jerry"),
+        Some(Date::new(2020, 1, 2).unwrap()),
+        |_| None,
+      ),
+      r#"<?xml version="1.0" encoding="utf-8"?>
+<feed xmlns="http://www.w3.org/2005/Atom">
+  <id>gemini://gem.rest/</id>
+  <title>Empty</title>
+  <updated>2020-01-02T00:00:00Z</updated>
+  <author><name>Tom &amp; Jerry</name></author>
+  <link href="gemini://gem.rest/" rel="alternate"/>
+</feed>
+"#
+    );
+    assert!(
+      !to_atom(&index, &base, None, None, |_| None).contains("<updated>")
+    );
+  }
+}
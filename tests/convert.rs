@@ -19,8 +19,12 @@
 #[cfg(test)]
 mod test {
   use germ::{
-    convert::{from_string, Target},
-    gemini_to_html, gemini_to_md,
+    ast::{borrowed, Ast, Node},
+    convert::{
+      from_ast, from_html, from_markdown, from_string, HtmlOptions,
+      LinkPlacement, LinkRuns, LinkStyle, NodeKind, Target,
+    },
+    gemini_to_html, gemini_to_md, gemini_to_text,
   };
 
   #[test]
@@ -30,7 +34,10 @@ mod test {
 
   #[test]
   fn convert_from_string_to_html_multi_line() {
-    assert_eq!(from_string("hi\n# hi", &Target::HTML), "<p>hi</p><h1>hi</h1>",);
+    assert_eq!(
+      from_string("hi\n# hi", &Target::HTML),
+      "<p>hi</p><h1 id=\"hi\">hi</h1>",
+    );
   }
 
   #[test]
@@ -41,6 +48,139 @@ mod test {
     );
   }
 
+  #[test]
+  fn convert_from_string_to_html_escapes_text() {
+    assert_eq!(
+      from_string(
+        "# <h>\n* a & b\n> \"q\"\n```<alt>\n</pre>\n```",
+        &Target::HTML
+      ),
+      "<h1 id=\"h\">&lt;h&gt;</h1><ul><li>a &amp; \
+       b</li></ul><blockquote>&quot;q&quot;</blockquote><pre>&lt;/pre&gt;\n</\
+       pre>",
+    );
+  }
+
+  #[test]
+  fn convert_from_string_to_html_escapes_link_locations() {
+    assert_eq!(
+      from_string("=> /a?b=\"c\"&d=' <e>", &Target::HTML),
+      "<a href=\"/a?b=&quot;c&quot;&amp;d=&#39;\">&lt;e&gt;</a><br>",
+    );
+  }
+
+  #[test]
+  fn convert_from_string_to_html_neutralises_dangerous_links() {
+    for to in [
+      "javascript:alert(1)",
+      "JaVaScRiPt:alert(1)",
+      "\u{1}vbscript:msgbox(1)",
+      "data:text/html,<script>alert(1)</script>",
+    ] {
+      assert_eq!(
+        from_string(&format!("=> {to} Hi"), &Target::HTML),
+        "<a>Hi</a><br>",
+      );
+    }
+
+    assert_eq!(
+      from_ast(
+        &Ast::from_nodes(vec![Node::Link {
+          to:   " java\nscript:alert(1)".to_string(),
+          text: None,
+        }]),
+        &Target::HTML
+      ),
+      "<a> java\nscript:alert(1)</a><br>",
+    );
+    assert_eq!(
+      from_string("=> javascript-notes.gmi", &Target::HTML),
+      "<a href=\"javascript-notes.gmi\">javascript-notes.gmi</a><br>",
+    );
+  }
+
+  #[test]
+  fn convert_from_string_to_trusted_html() {
+    assert_eq!(
+      from_string(
+        "<b>hi</b>\n=> javascript:go() <i>Go</i>",
+        &Target::TrustedHTML
+      ),
+      "<p><b>hi</b></p><a href=\"javascript:go()\"><i>Go</i></a><br>",
+    );
+  }
+
+  #[test]
+  fn convert_with_html_options_matches_target() {
+    let ast = Ast::from_string("# <Hi>\n\n=> javascript:x X\n* 1");
+
+    assert_eq!(HtmlOptions::new().to_html(&ast), from_ast(&ast, &Target::HTML));
+    assert_eq!(
+      HtmlOptions::new().trusted(true).to_html(&ast),
+      from_ast(&ast, &Target::TrustedHTML)
+    );
+  }
+
+  #[test]
+  fn convert_with_html_options_to_document() {
+    assert_eq!(
+      HtmlOptions::new()
+        .document(true)
+        .title("A & B")
+        .css("p { margin: 0; }")
+        .to_html(&Ast::from_string("# Heading\ntext")),
+      "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>A \
+       &amp; B</title>\n<style>p { margin: 0; }</style>\n</head>\n<body>\n<h1 \
+       id=\"heading\">Heading</h1><p>text</p>\n</body>\n</html>\n"
+    );
+    assert_eq!(
+      HtmlOptions::new().document(true).to_html(&Ast::from_string("text")),
+      "<!DOCTYPE html>\n<html>\n<head>\n<meta \
+       charset=\"utf-8\">\n</head>\n<body>\n<p>text</p>\n</body>\n</html>\n"
+    );
+    assert_eq!(
+      HtmlOptions::new()
+        .document(true)
+        .links(LinkRuns::List)
+        .to_html(&Ast::from_string("=> /a\n## Later\n# Title")),
+      "<!DOCTYPE html>\n<html>\n<head>\n<meta \
+       charset=\"utf-8\">\n<title>Later</title>\n</head>\n<body>\n<ul><li><a \
+       href=\"/a\">/a</a></li></ul><h2 id=\"later\">Later</h2><h1 \
+       id=\"title\">Title</h1>\n</body>\n</html>\n"
+    );
+  }
+
+  #[test]
+  fn convert_with_html_options_classes_and_link_runs() {
+    let ast =
+      Ast::from_string("=> /a\n=> /b\ntext\n\n=> /c\n* i\n> q\n```\np\n```");
+    let options = HtmlOptions::new()
+      .class(NodeKind::Text, "t")
+      .class(NodeKind::Link, "l")
+      .class(NodeKind::List, "u")
+      .class(NodeKind::Blockquote, "q")
+      .class(NodeKind::PreformattedText, "p")
+      .class(NodeKind::Whitespace, "w");
+
+    assert_eq!(options.class_name(NodeKind::Link), Some("l"));
+    assert_eq!(options.class_name(NodeKind::Heading), None);
+    assert_eq!(
+      options.clone().to_html(&ast),
+      "<a class=\"l\" href=\"/a\">/a</a><br><a class=\"l\" \
+       href=\"/b\">/b</a><br><p class=\"t\">text</p><a class=\"l\" \
+       href=\"/c\">/c</a><br><ul class=\"u\"><li>i</li></ul><blockquote \
+       class=\"q\">q</blockquote><pre class=\"p\">p\n</pre>"
+    );
+    assert_eq!(
+      options.links(LinkRuns::List).break_whitespace(true).to_html(&ast),
+      "<ul><li><a class=\"l\" href=\"/a\">/a</a></li><li><a class=\"l\" \
+       href=\"/b\">/b</a></li></ul><p class=\"t\">text</p><br \
+       class=\"w\"><ul><li><a class=\"l\" href=\"/c\">/c</a></li></ul><ul \
+       class=\"u\"><li>i</li></ul><blockquote class=\"q\">q</blockquote><pre \
+       class=\"p\">p\n</pre>"
+    );
+  }
+
   #[test]
   fn convert_from_string_to_markdown_single_line() {
     assert_eq!(from_string("hi", &Target::Markdown), "hi\n",);
@@ -48,7 +188,10 @@ mod test {
 
   #[test]
   fn convert_from_string_to_markdown_multi_line() {
-    assert_eq!(from_string("hi\n# hi", &Target::Markdown), "hi\n# hi\n",);
+    assert_eq!(
+      from_string("hi\n# hi", &Target::Markdown),
+      "hi\n# <a id=\"hi\"></a>hi\n",
+    );
   }
 
   #[test]
@@ -63,4 +206,224 @@ mod test {
   fn convert_from_string_to_markdown_single_macro_expression() {
     assert_eq!(gemini_to_md!("=> /to hello !"), "[hello !](/to)\n",);
   }
+
+  #[test]
+  fn convert_from_borrowed_ast_to_html() {
+    assert_eq!(
+      from_ast(borrowed::Ast::from_value("hi\n# hi"), &Target::HTML),
+      "<p>hi</p><h1 id=\"hi\">hi</h1>",
+    );
+  }
+
+  #[test]
+  fn convert_from_string_to_plain_text() {
+    assert_eq!(
+      from_string(
+        "# Hi\n\ntext\n=> /a A\n=> /b\n* 1\n* 2\n> q\n```alt\n  pre\n```",
+        &Target::PlainText(LinkStyle::Inline),
+      ),
+      "Hi\n\ntext\nA </a>\n/b\n• 1\n• 2\n    q\n  pre\n",
+    );
+  }
+
+  #[test]
+  fn convert_from_string_to_plain_text_with_link_references() {
+    assert_eq!(
+      from_string(
+        "=> /a A\nText\n=> /b\n=> /c C",
+        &Target::PlainText(LinkStyle::References),
+      ),
+      "A [1]\nText\n/b\nC [2]\n\n[1] /a\n[2] /c\n",
+    );
+  }
+
+  #[test]
+  fn convert_from_string_to_plain_text_macro_expression() {
+    assert_eq!(gemini_to_text!("=> /to hello !"), "hello ! </to>\n",);
+  }
+
+  #[test]
+  fn convert_from_markdown_lifts_links_after_their_block() {
+    assert_eq!(
+      from_markdown(
+        "A [link](/a \"title\") and ![an image](/b.png), <gemini://c>.\n\n> \
+         [quoted][q]\n\n[q]: /q"
+      )
+      .to_gemtext(),
+      "A link and an image, gemini://c.\n=> /a link\n=> /b.png an image\n=> \
+       gemini://c\n\n> quoted\n=> /q quoted"
+    );
+  }
+
+  #[test]
+  fn convert_from_markdown_strips_inline_markup() {
+    assert_eq!(
+      from_markdown(
+        "**Bold**, _emphasis_, ~~struck~~, `co*de`, snake_case, \\*, 2 * 3"
+      )
+      .inner(),
+      &[Node::Text(
+        "Bold, emphasis, struck, co*de, snake_case, *, 2 * 3".to_string()
+      )]
+    );
+  }
+
+  #[test]
+  fn convert_from_markdown_skips_empty_lines_of_paragraphs() {
+    assert_eq!(
+      from_markdown("\\\n\na\\\n\\\nb\n\n[](/x)").inner(),
+      &[
+        Node::Text("a".to_string()),
+        Node::Text("b".to_string()),
+        Node::Whitespace,
+        Node::Link { to: "/x".to_string(), text: None },
+      ]
+    );
+  }
+
+  #[test]
+  fn convert_from_markdown_blocks() {
+    assert_eq!(
+      from_markdown(
+        "Title\n=====\n\n##### Deep ###\none\ntwo  \nthree\n\n1. a\n   \
+         continued\n   - b\n\n     c\n2) d\n\n---\n\n| a | b |\n|:--|--:|\n| \
+         1 | 2 |\n\n~~~ rust ignore\nfn main() {}\n~~~\n\n    indented"
+      ),
+      Ast::from_nodes(vec![
+        Node::Heading { level: 1, text: "Title".to_string() },
+        Node::Whitespace,
+        Node::Heading { level: 3, text: "Deep".to_string() },
+        Node::Text("one two".to_string()),
+        Node::Text("three".to_string()),
+        Node::Whitespace,
+        Node::List(vec![
+          "a continued".to_string(),
+          "b c".to_string(),
+          "d".to_string(),
+        ]),
+        Node::Whitespace,
+        Node::PreformattedText {
+          alt_text: None,
+          text:     "| a | b |\n|:--|--:|\n| 1 | 2 |\n".to_string(),
+        },
+        Node::Whitespace,
+        Node::PreformattedText {
+          alt_text: Some("rust".to_string()),
+          text:     "fn main() {}\n".to_string(),
+        },
+        Node::Whitespace,
+        Node::PreformattedText {
+          alt_text: None,
+          text:     "indented\n".to_string(),
+        },
+      ])
+    );
+  }
+
+  #[test]
+  fn convert_from_markdown_strips_fence_indentation_by_columns() {
+    for (markdown, text) in [
+      ("  ```\n\t\tx\n   y\n```", "\t\tx\n y\n"),
+      ("   ```\n\t x\n    y\n```", "\t x\n y\n"),
+      (" ```\n\u{3000}x\n```", "x\n"),
+      ("  ```\n\u{3000}\u{3000}\u{3000}x\n```", "\u{3000}x\n"),
+      ("\u{3000}\u{3000}\u{3000}\u{3000}\u{3000}x", "\u{3000}x\n"),
+    ] {
+      assert_eq!(
+        from_markdown(markdown).inner(),
+        &[Node::PreformattedText {
+          alt_text: None,
+          text:     text.to_string(),
+        }],
+        "{markdown:?}"
+      );
+    }
+  }
+
+  #[test]
+  fn convert_from_markdown_round_trips_through_gemtext() {
+    let ast = from_markdown(
+      "\\* x\n\n\\# x\n\n\\> x\n\n\\=> x\n\n\\`x`\n\n[x](<a \
+       b>)\n\n````\n```inner\n````",
+    );
+
+    assert_eq!(
+      ast.to_gemtext(),
+      " * x\n\n # x\n\n > x\n\n => x\n\n `x`\n\nx\n=> a%20b x\n\n```\n \
+       ```inner\n```"
+    );
+    assert_eq!(Ast::from_string(ast.to_gemtext()), ast);
+  }
+
+  #[test]
+  fn convert_from_html_round_trips_through_gemtext() {
+    let ast = from_html(
+      "<p>* x</p><p># x</p><p>&gt; x</p><p>=&gt; x</p><p>```x</p><a href=\"a \
+       b\">x</a><pre>```inner\n```</pre>",
+      LinkPlacement::default(),
+    );
+
+    assert_eq!(
+      ast.to_gemtext(),
+      " * x\n # x\n > x\n => x\n ```x\nx\n=> a%20b x\n```\n ```inner\n \
+       ```\n```"
+    );
+    assert_eq!(Ast::from_string(ast.to_gemtext()), ast);
+  }
+
+  #[test]
+  fn convert_from_html_blocks() {
+    assert_eq!(
+      from_html(
+        "<!DOCTYPE html><html><head><title>T</title><style>p { \
+         }</style></head><body><script>if (a < b) \
+         {}</script><h1>One</h1><h2>Two</h2><h6>Six</h6><p>A &amp; \
+         B&#33;&nbsp;<br>C<!-- comment \
+         --></p><blockquote><p>Q</p></blockquote><ol><li>1</\
+         li><li>2<ol><li>3</li></ol></li></ol><pre \
+         class=\"language-rust\">\nfn main() {\n  \
+         &lt;x&gt;\n}</pre><pre><code \
+         class=\"lang-sh\">ls</code></pre>Trailing</body></html>",
+        LinkPlacement::default(),
+      ),
+      Ast::from_nodes(vec![
+        Node::Heading { level: 1, text: "One".to_string() },
+        Node::Heading { level: 2, text: "Two".to_string() },
+        Node::Heading { level: 3, text: "Six".to_string() },
+        Node::Text("A & B!\u{a0}".to_string()),
+        Node::Text("C".to_string()),
+        Node::Blockquote("Q".to_string()),
+        Node::List(vec!["1".to_string(), "2".to_string(), "3".to_string()]),
+        Node::PreformattedText {
+          alt_text: Some("rust".to_string()),
+          text:     "fn main() {\n  <x>\n}\n".to_string(),
+        },
+        Node::PreformattedText {
+          alt_text: Some("sh".to_string()),
+          text:     "ls\n".to_string(),
+        },
+        Node::Text("Trailing".to_string()),
+      ])
+    );
+  }
+
+  #[test]
+  fn convert_from_html_links() {
+    let html =
+      "<p><a href='/a'>A</a> and <a href=/b><img src=x></a></p><ul><li><a \
+       href=\"/c\">C</a></li></ul><nav><nav></nav><a href=\"/\">Home</a></nav>";
+
+    assert_eq!(
+      from_html(html, LinkPlacement::Paragraph).to_gemtext(),
+      "A and\n=> /a A\n=> /b\n* C\n=> /c C"
+    );
+    assert_eq!(
+      from_html(html, LinkPlacement::Inline).to_gemtext(),
+      "=> /a A\nand\n=> /b\n* C\n=> /c C"
+    );
+    assert_eq!(
+      from_html(html, LinkPlacement::Footnotes).to_gemtext(),
+      "A [1] and [2]\n* C [3]\n\n=> /a [1] A\n=> /b [2]\n=> /c [3] C"
+    );
+  }
 }
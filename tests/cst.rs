@@ -0,0 +1,105 @@
+// This file is part of Germ <https://github.com/gemrest/germ>.
+// Copyright (C) 2022-2022 Fuwn <contact@fuwn.me>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+//
+// Copyright (C) 2022-2022 Fuwn <contact@fuwn.me>
+// SPDX-License-Identifier: GPL-3.0-only
+
+#[cfg(test)]
+mod test {
+  use germ::{
+    ast::{
+      cst::{Cst, LineEnding, Node},
+      Ast,
+    },
+    EXAMPLE_GEMTEXT,
+  };
+
+  const SOURCES: [&str; 12] = [
+    EXAMPLE_GEMTEXT,
+    "=>\t/x  Foo\n*foo\n",
+    "=> /test  hi there \r\n```alt\r\nbody\r\n\r\n```trailing\r\n",
+    "a\n```\nunterminated\n",
+    "a\n```\r\nunterminated",
+    "=>\n=\n=>/no-space\n",
+    "#### Deep\n#Close\n>quote\n> \n",
+    "* a\n*  b \r\n\n\n",
+    "``\n`é\n",
+    "trailing\r",
+    "",
+    "\n",
+  ];
+
+  #[test]
+  fn regenerates_the_source_byte_for_byte() {
+    for source in SOURCES {
+      assert_eq!(Cst::from_value(source).to_gemtext(), source);
+    }
+  }
+
+  #[test]
+  fn lowers_into_the_same_ast_as_the_parser() {
+    for source in SOURCES {
+      assert_eq!(
+        Cst::from_value(source).lower().into_owned(),
+        Ast::from_string(source),
+        "{source:?}"
+      );
+    }
+  }
+
+  #[test]
+  fn splits_lines_into_their_parts() {
+    let cst = Cst::from_value("=>\t/x  Foo \r\n##  Hi\n```rust\n```");
+
+    let Node::Link(link) = &cst.nodes()[0] else { panic!() };
+
+    assert_eq!(link.marker(), "=>");
+    assert_eq!(link.spacing(), "\t");
+    assert_eq!(link.to(), "/x");
+    assert_eq!(link.separator(), "  ");
+    assert_eq!(link.text(), "Foo ");
+    assert_eq!(link.ending(), Some(LineEnding::CrLf));
+
+    let Node::Heading(heading) = &cst.nodes()[1] else { panic!() };
+
+    assert_eq!(heading.marker(), "##");
+    assert_eq!(heading.spacing(), "  ");
+    assert_eq!(heading.text(), "Hi");
+
+    let Node::PreformattedText { open, body, close } = &cst.nodes()[2] else {
+      panic!()
+    };
+
+    assert_eq!(open.text(), "rust");
+    assert!(body.is_empty());
+    assert_eq!(close.as_ref().unwrap().ending(), None);
+  }
+
+  #[test]
+  fn edits_only_touch_the_edited_line() {
+    let mut cst = Cst::from_value("#  Title\r\n=>\t/old  Old \r\n*item\n");
+
+    if let Node::Link(link) = &mut cst.nodes_mut()[1] {
+      *link.to_mut() = "/new".into();
+      *link.text_mut() = "New ".into();
+    }
+
+    assert_eq!(cst.to_gemtext(), "#  Title\r\n=>\t/new  New \r\n*item\n");
+    assert_eq!(cst.lower().into_owned().inner()[1], germ::ast::Node::Link {
+      to:   "/new".to_string(),
+      text: Some("New".to_string()),
+    });
+  }
+}
@@ -0,0 +1,142 @@
+// This file is part of Germ <https://github.com/gemrest/germ>.
+// Copyright (C) 2022-2022 Fuwn <contact@fuwn.me>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+//
+// Copyright (C) 2022-2022 Fuwn <contact@fuwn.me>
+// SPDX-License-Identifier: GPL-3.0-only
+
+#[cfg(test)]
+mod test {
+  use germ::{
+    ast::{diff, Ast, Node, Operation},
+    EXAMPLE_GEMTEXT,
+  };
+
+  #[test]
+  fn diff_equal_asts_is_empty() {
+    let ast = Ast::from_string(EXAMPLE_GEMTEXT);
+
+    assert!(diff(&ast, &ast).is_empty());
+  }
+
+  #[test]
+  fn diff_inserts_and_deletes() {
+    let old = Ast::from_string("a\n=> /b B\nc");
+    let new = Ast::from_string("a\nc\n> d");
+
+    assert_eq!(diff(&old, &new).operations(), &[
+      Operation::Delete {
+        index: 1,
+        node:  Node::Link {
+          to:   "/b".to_string(),
+          text: Some("B".to_string()),
+        },
+      },
+      Operation::Insert { index: 2, node: Node::Blockquote("d".to_string()) },
+    ]);
+  }
+
+  #[test]
+  fn diff_modifies_nodes_of_the_same_type_only() {
+    let old = Ast::from_string("a\n# b");
+    let new = Ast::from_string("c\n> b");
+
+    assert_eq!(diff(&old, &new).operations(), &[
+      Operation::Modify {
+        old_index: 0,
+        new_index: 0,
+        old:       Node::Text("a".to_string()),
+        new:       Node::Text("c".to_string()),
+      },
+      Operation::Delete {
+        index: 1,
+        node:  Node::Heading { level: 1, text: "b".to_string() },
+      },
+      Operation::Insert { index: 1, node: Node::Blockquote("b".to_string()) },
+    ]);
+  }
+
+  #[test]
+  fn diff_applies_as_a_patch() {
+    for (old, new) in [
+      ("", EXAMPLE_GEMTEXT),
+      (EXAMPLE_GEMTEXT, ""),
+      ("a\nb\nc\nd", "d\nc\nb\na"),
+      ("# A\n\n=> /1\n=> /2\n\n# B\n* x", "# B\n* x\n* y\n\n# A\n=> /2\n=> /3"),
+    ] {
+      let (old, new) = (Ast::from_string(old), Ast::from_string(new));
+
+      assert_eq!(diff(&old, &new).apply(&old), Ok(new));
+    }
+  }
+
+  #[test]
+  fn diff_long_asts_with_few_changes() {
+    let lines = (0..100_000).map(|line| line.to_string()).collect::<Vec<_>>();
+    let old = Ast::from_string(lines.join("\n"));
+    let mut new = lines;
+
+    new[50_000] = "changed".to_string();
+    new.insert(75_000, "> inserted".to_string());
+
+    let new = Ast::from_string(new.join("\n"));
+    let diff = diff(&old, &new);
+
+    assert_eq!(diff.operations(), &[
+      Operation::Modify {
+        old_index: 50_000,
+        new_index: 50_000,
+        old:       Node::Text("50000".to_string()),
+        new:       Node::Text("changed".to_string()),
+      },
+      Operation::Insert {
+        index: 75_000,
+        node:  Node::Blockquote("inserted".to_string()),
+      },
+    ]);
+    assert_eq!(diff.apply(&old), Ok(new));
+  }
+
+  #[test]
+  fn diff_conflicts_with_other_asts() {
+    let old = Ast::from_string("a\nb");
+    let new = Ast::from_string("a\nc");
+    let conflict =
+      diff(&old, &new).apply(&Ast::from_string("a\nd")).unwrap_err();
+
+    assert_eq!(conflict.operation(), 0);
+  }
+
+  #[test]
+  fn diff_keeps_untouched_nodes_when_applied() {
+    let old = Ast::from_string("a\nb");
+    let new = Ast::from_string("a\nc");
+
+    assert_eq!(
+      diff(&old, &new).apply(&Ast::from_string("a\nb\nd")),
+      Ok(Ast::from_string("a\nc\nd"))
+    );
+  }
+
+  #[test]
+  fn diff_to_gemtext_prefixes_every_line() {
+    let old = Ast::from_string("```rs\nfn main() {}\n```\n");
+    let new = Ast::from_string("");
+
+    assert_eq!(
+      diff(&old, &new).to_gemtext(),
+      "@@ -0 @@\n-```rs\n-fn main() {}\n-```\n@@ -1 @@\n-\n"
+    );
+  }
+}
@@ -0,0 +1,104 @@
+// This file is part of Germ <https://github.com/gemrest/germ>.
+// Copyright (C) 2022-2022 Fuwn <contact@fuwn.me>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+//
+// Copyright (C) 2022-2022 Fuwn <contact@fuwn.me>
+// SPDX-License-Identifier: GPL-3.0-only
+
+#[cfg(test)]
+mod test {
+  use germ::{
+    ast::Ast,
+    feed::{Date, Entry, Feed, InvalidDate},
+  };
+
+  fn base() -> url::Url {
+    url::Url::parse("gemini://gem.rest/gemlog/").unwrap()
+  }
+
+  #[test]
+  fn parse_dates() {
+    assert_eq!("2024-02-29".parse(), Ok(Date::new(2024, 2, 29).unwrap()));
+    assert_eq!("2023-02-29".parse::<Date>(), Err(InvalidDate));
+    assert_eq!("2024-13-01".parse::<Date>(), Err(InvalidDate));
+    assert_eq!("2024-1-01".parse::<Date>(), Err(InvalidDate));
+    assert_eq!("+024-01-01".parse::<Date>(), Err(InvalidDate));
+    assert!(Date::new(2024, 1, 1) < Date::new(2024, 1, 2));
+  }
+
+  #[test]
+  fn read_a_feed() {
+    let feed = Feed::from_ast(
+      &Ast::from_string(
+        "Preamble\n# My Gemlog\n## Subtitle\n=> post-3.gmi 2024-05-03 - Third \
+         - with dashes\n=> /post-2.gmi 2024-05-02 Second\n=> post-1.gmi \
+         2024-05-01\n=> /about.gmi About\n=> /bad.gmi 2024-99-01 Bad date\n=> \
+         -post.gmi 2024-05-04 -post",
+      ),
+      &base(),
+    );
+
+    assert_eq!(feed.title(), Some("My Gemlog"));
+    assert_eq!(
+      feed
+        .entries()
+        .iter()
+        .map(|entry| (
+          entry.url().as_str(),
+          entry.date().to_string(),
+          entry.title()
+        ))
+        .collect::<Vec<_>>(),
+      vec![
+        (
+          "gemini://gem.rest/gemlog/post-3.gmi",
+          "2024-05-03".to_string(),
+          "Third - with dashes"
+        ),
+        ("gemini://gem.rest/post-2.gmi", "2024-05-02".to_string(), "Second"),
+        ("gemini://gem.rest/gemlog/post-1.gmi", "2024-05-01".to_string(), ""),
+        (
+          "gemini://gem.rest/gemlog/-post.gmi",
+          "2024-05-04".to_string(),
+          "-post"
+        ),
+      ]
+    );
+  }
+
+  #[test]
+  fn write_and_read_back_a_feed() {
+    let mut feed = Feed::new("My Gemlog");
+
+    for day in 1..=3 {
+      feed = feed.entry(Entry::new(
+        base().join(&format!("{day}.gmi")).unwrap(),
+        Date::new(2024, 5, day).unwrap(),
+        format!("Post {day}"),
+      ));
+    }
+
+    feed.entries_mut().sort_by_key(|entry| std::cmp::Reverse(entry.date()));
+
+    let ast = feed.to_ast();
+
+    assert_eq!(
+      ast.to_gemtext(),
+      "# My Gemlog\n\n=> gemini://gem.rest/gemlog/3.gmi 2024-05-03 - Post \
+       3\n=> gemini://gem.rest/gemlog/2.gmi 2024-05-02 - Post 2\n=> \
+       gemini://gem.rest/gemlog/1.gmi 2024-05-01 - Post 1"
+    );
+    assert_eq!(Feed::from_ast(&ast, &base()), feed);
+  }
+}
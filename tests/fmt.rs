@@ -0,0 +1,78 @@
+// This file is part of Germ <https://github.com/gemrest/germ>.
+// Copyright (C) 2022-2022 Fuwn <contact@fuwn.me>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+//
+// Copyright (C) 2022-2022 Fuwn <contact@fuwn.me>
+// SPDX-License-Identifier: GPL-3.0-only
+
+#[cfg(test)]
+mod test {
+  use germ::{
+    fmt::{check, format},
+    EXAMPLE_GEMTEXT,
+  };
+
+  #[test]
+  fn normalise_line_identifiers() {
+    assert_eq!(
+      format("=>\t/x  Foo  Bar \n*a\n*   b\n>quote\n###Deep  \n"),
+      "=> /x Foo Bar\n* a\n* b\n> quote\n\n### Deep\n"
+    );
+  }
+
+  #[test]
+  fn collapse_blank_lines() {
+    assert_eq!(format("\n\na\n\n\n   \nb\nc\n\n\n"), "a\n\nb\nc\n");
+  }
+
+  #[test]
+  fn space_out_headings_and_preformatted_blocks() {
+    assert_eq!(
+      format("# A\n## B\ntext\n```alt  \nx\n```\n* item"),
+      "# A\n\n## B\n\ntext\n\n```alt\nx\n```\n\n* item\n"
+    );
+  }
+
+  #[test]
+  fn keep_preformatted_content_byte_identical() {
+    assert_eq!(
+      format("text\r\n```\r\n  a  \r\n\r\n\tb\r\n```\r\n"),
+      "text\n\n```\n  a  \r\n\r\n\tb\r\n```\n"
+    );
+    assert_eq!(format("```\nunterminated  "), "```\nunterminated  \n```\n");
+  }
+
+  #[test]
+  fn formatting_is_idempotent() {
+    for source in [
+      EXAMPLE_GEMTEXT,
+      "text\r\n```\r\n  a  \r\n```\r\n",
+      "=>\n#\n*\n>\n",
+      "",
+    ] {
+      let formatted = format(source);
+
+      assert!(check(&formatted), "{source:?}");
+      assert_eq!(format(&formatted), formatted);
+    }
+  }
+
+  #[test]
+  fn check_reports_unformatted_gemtext() {
+    assert!(check(""));
+    assert!(check("# Title\n\n=> /a A\n"));
+    assert!(!check("# Title\n\n=> /a A"));
+    assert!(!check("# Title\n\n=>  /a A\n"));
+  }
+}
@@ -0,0 +1,119 @@
+// This file is part of Germ <https://github.com/gemrest/germ>.
+// Copyright (C) 2022-2022 Fuwn <contact@fuwn.me>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+//
+// Copyright (C) 2022-2022 Fuwn <contact@fuwn.me>
+// SPDX-License-Identifier: GPL-3.0-only
+
+#[cfg(test)]
+mod test {
+  use germ::{
+    ast::Ast,
+    convert::{
+      from_ast,
+      highlight::{Highlighter, Language, Theme, TokenKind},
+      HtmlOptions,
+      NodeKind,
+      Target,
+    },
+  };
+
+  #[test]
+  fn highlight_falls_back_to_plain_pre() {
+    for source in
+      ["```\nlet x = 1;\n```", "```brainfuck\n+[-]\n```", "```\n```"]
+    {
+      let ast = Ast::from_string(source);
+
+      assert_eq!(
+        Highlighter::new().to_html(&ast, &HtmlOptions::new()),
+        from_ast(&ast, &Target::HTML)
+      );
+    }
+  }
+
+  #[test]
+  fn highlight_reads_the_first_word_of_alt_text() {
+    assert_eq!(
+      Highlighter::new().to_html(
+        &Ast::from_string("```Python example\nx = 'a' # b\n```"),
+        &HtmlOptions::new()
+      ),
+      "<pre><code class=\"language-python\">x = <span \
+       class=\"string\">&#39;a&#39;</span> <span class=\"comment\"># \
+       b</span>\n</code></pre>"
+    );
+  }
+
+  #[test]
+  fn highlight_follows_html_options() {
+    let ast = Ast::from_string("```c\n1\n```\n```\n<b>\n```");
+
+    assert_eq!(
+      Highlighter::new().to_html(
+        &ast,
+        &HtmlOptions::new()
+          .class(NodeKind::PreformattedText, "code")
+          .trusted(true)
+      ),
+      "<pre class=\"code\"><code class=\"language-c\"><span \
+       class=\"number\">1</span>\n</code></pre><pre class=\"code\"><b>\n</pre>"
+    );
+  }
+
+  #[test]
+  fn highlight_escapes_html() {
+    assert_eq!(
+      Highlighter::new().highlight("c", "/* <b> */ a < \"&\"").unwrap(),
+      "<span class=\"comment\">/* &lt;b&gt; */</span> a &lt; <span \
+       class=\"string\">&quot;&amp;&quot;</span>"
+    );
+  }
+
+  #[test]
+  fn highlight_keeps_escaped_quotes_in_strings() {
+    assert_eq!(
+      Highlighter::new().highlight("rs", r#""a\"b" 1.5"#).unwrap(),
+      "<span class=\"string\">&quot;a\\&quot;b&quot;</span> <span \
+       class=\"number\">1.5</span>"
+    );
+  }
+
+  #[test]
+  fn highlight_languages_and_theme_are_configurable() {
+    let highlighter = Highlighter::new()
+      .languages([Language::new(["ini"]).line_comment(";")])
+      .theme(Theme::new().class(TokenKind::Comment, "c"));
+
+    assert_eq!(highlighter.highlight("rust", "fn"), None);
+    assert_eq!(
+      highlighter.highlight("INI", "a=1 ; b").unwrap(),
+      "a=<span class=\"number\">1</span> <span class=\"c\">; b</span>"
+    );
+  }
+
+  #[test]
+  fn highlight_ignores_empty_comment_markers() {
+    let highlighter = Highlighter::new().languages([
+      Language::new(["empty"]).line_comment("").block_comment("", ""),
+      Language::new(["newline"]).line_comment("\n"),
+    ]);
+
+    assert_eq!(highlighter.highlight("empty", "a // b").unwrap(), "a // b");
+    assert_eq!(
+      highlighter.highlight("newline", "a\nb").unwrap(),
+      "a<span class=\"comment\">\n</span>b"
+    );
+  }
+}
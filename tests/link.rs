@@ -0,0 +1,86 @@
+// This file is part of Germ <https://github.com/gemrest/germ>.
+// Copyright (C) 2022-2022 Fuwn <contact@fuwn.me>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+//
+// Copyright (C) 2022-2022 Fuwn <contact@fuwn.me>
+// SPDX-License-Identifier: GPL-3.0-only
+
+#[cfg(test)]
+mod test {
+  use germ::ast::{Ast, LinkTarget, Node, RelativeReference};
+
+  fn target(to: &str) -> Result<LinkTarget, url::ParseError> {
+    Node::Link { to: to.to_string(), text: None }.link_target().unwrap()
+  }
+
+  #[test]
+  fn link_targets_are_typed() {
+    assert_eq!(
+      target("gemini://gem.rest/"),
+      Ok(LinkTarget::Url(url::Url::parse("gemini://gem.rest/").unwrap()))
+    );
+
+    for (to, reference) in [
+      ("//gem.rest/", RelativeReference::NetworkPath("//gem.rest/".into())),
+      ("/foo", RelativeReference::AbsolutePath("/foo".into())),
+      ("../bar", RelativeReference::RelativePath("../bar".into())),
+      ("?query", RelativeReference::Query("?query".into())),
+      ("#heading", RelativeReference::Fragment("#heading".into())),
+    ] {
+      assert_eq!(target(to), Ok(LinkTarget::Relative(reference)));
+    }
+
+    assert_eq!(target("http://[::1"), Err(url::ParseError::InvalidIpv6Address));
+    assert_eq!(Node::Text("/foo".to_string()).link_target(), None);
+  }
+
+  #[test]
+  fn resolve_links_against_a_base() {
+    let mut ast = Ast::from_string(
+      "# Links\n=> /foo Foo\n=> ../bar\n=> ?q\n=> https://a.b/",
+    );
+
+    assert!(
+      ast
+        .resolve_links(&url::Url::parse("gemini://gem.rest/x/y/z.gmi").unwrap())
+        .is_empty()
+    );
+    assert_eq!(
+      ast.to_gemtext(),
+      "# Links\n=> gemini://gem.rest/foo Foo\n=> gemini://gem.rest/x/bar\n=> \
+       gemini://gem.rest/x/y/z.gmi?q\n=> https://a.b/"
+    );
+  }
+
+  #[test]
+  fn invalid_links_are_reported() {
+    let mut ast = Ast::from_string("=> /ok\n=> /also-ok Fine\n=> http://[::1");
+    let invalid = ast.resolve_links(&url::Url::parse("gemini://a.b/").unwrap());
+
+    assert_eq!(invalid.len(), 1);
+    assert_eq!(invalid[0].to(), "http://[::1");
+    assert_eq!(
+      invalid[0].to_string(),
+      "3:4: invalid link location `http://[::1`: invalid IPv6 address"
+    );
+    assert!(ast.spans().is_empty());
+
+    let mut ast = Ast::from_string("=> /x");
+
+    assert_eq!(
+      ast.resolve_links(&url::Url::parse("mailto:a@b.c").unwrap())[0].error(),
+      url::ParseError::RelativeUrlWithCannotBeABaseBase
+    );
+  }
+}
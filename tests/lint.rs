@@ -0,0 +1,145 @@
+// This file is part of Germ <https://github.com/gemrest/germ>.
+// Copyright (C) 2022-2022 Fuwn <contact@fuwn.me>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+//
+// Copyright (C) 2022-2022 Fuwn <contact@fuwn.me>
+// SPDX-License-Identifier: GPL-3.0-only
+
+#[cfg(test)]
+mod test {
+  use germ::{
+    ast::Ast,
+    lint::{Linter, Rule},
+  };
+
+  fn rules(linter: &Linter, source: &str) -> Vec<Rule> {
+    linter.lint(source).iter().map(germ::lint::Warning::rule).collect()
+  }
+
+  #[test]
+  fn clean_gemtext_has_no_warnings() {
+    assert!(
+      Linter::new()
+        .lint("# Hi\n\n## There\n=> /about About\n```rust\nfn main() {}\n```\n")
+        .is_empty()
+    );
+  }
+
+  #[test]
+  fn empty_links() {
+    assert_eq!(rules(&Linter::new(), "=>\n=>   "), vec![
+      Rule::EmptyLink,
+      Rule::EmptyLink,
+      Rule::TrailingWhitespace,
+    ]);
+    assert!(rules(&Linter::new(), "==").is_empty());
+    assert!(rules(&Linter::new(), "=x").is_empty());
+  }
+
+  #[test]
+  fn escaping_links() {
+    let linter = Linter::new().path("/gemlog/");
+
+    assert_eq!(rules(&linter, "=> ../index.gmi\n=> ./../../x"), vec![
+      Rule::EscapingLink
+    ]);
+    assert!(rules(&linter, "=> gemini://gem.rest/../../x").is_empty());
+    assert!(rules(&linter, "=> //gem.rest/../../x").is_empty());
+    assert_eq!(rules(&linter, "=> /../x"), vec![Rule::EscapingLink]);
+  }
+
+  #[test]
+  fn escaping_link_span_points_at_the_location() {
+    let warnings = Linter::new().lint("=> ../x Up");
+
+    assert_eq!(warnings[0].span().unwrap().range(), 3..7);
+  }
+
+  #[test]
+  fn missing_alt_text() {
+    assert_eq!(rules(&Linter::new(), "```\nhi\n```\n```  \nhi\n```"), vec![
+      Rule::MissingAltText,
+      Rule::MissingAltText,
+    ]);
+  }
+
+  #[test]
+  fn skipped_heading_levels() {
+    assert_eq!(
+      rules(&Linter::new(), "## Start\n# Up\n### Skip\n## Fine\n### Fine"),
+      vec![Rule::SkippedHeadingLevel]
+    );
+  }
+
+  #[test]
+  fn trailing_whitespace_outside_of_preformatted_blocks() {
+    let warnings = Linter::new().lint("a\r\nb \t\r\n```alt\nc  \n```");
+
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].rule(), Rule::TrailingWhitespace);
+    assert_eq!(warnings[0].span().unwrap().start().line(), 2);
+    assert_eq!(warnings[0].span().unwrap().range(), 4..6);
+  }
+
+  #[test]
+  fn broken_link_syntax() {
+    assert_eq!(
+      rules(&Linter::new(), "=>/x\nSee => /x\n =>\n=/x\n=> /x"),
+      vec![
+        Rule::BrokenLinkSyntax,
+        Rule::BrokenLinkSyntax,
+        Rule::BrokenLinkSyntax,
+        Rule::BrokenLinkSyntax,
+      ]
+    );
+  }
+
+  #[test]
+  fn long_lines() {
+    let linter = Linter::new().max_line_length(4);
+
+    assert_eq!(rules(&linter, "four\nfive!\n```\nlonger\n```"), vec![
+      Rule::LongLine,
+      Rule::MissingAltText,
+      Rule::LongLine,
+    ]);
+  }
+
+  #[test]
+  fn rules_can_be_toggled() {
+    let linter = Linter::new().disable(Rule::EmptyLink);
+
+    assert!(!linter.is_enabled(Rule::EmptyLink));
+    assert!(linter.lint("=>").is_empty());
+    assert_eq!(rules(&linter.enable(Rule::EmptyLink), "=>"), vec![
+      Rule::EmptyLink
+    ]);
+  }
+
+  #[test]
+  fn lint_ast_without_spans() {
+    let warnings =
+      Linter::new().lint_ast(&Ast::from_nodes(vec![germ::ast::Node::Link {
+        to:   "../x".to_string(),
+        text: None,
+      }]));
+
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].span(), None);
+    assert_eq!(
+      warnings[0].to_string(),
+      "escaping-link: relative link resolves outside of the capsule"
+    );
+  }
+}
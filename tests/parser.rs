@@ -0,0 +1,90 @@
+// This file is part of Germ <https://github.com/gemrest/germ>.
+// Copyright (C) 2022-2022 Fuwn <contact@fuwn.me>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+//
+// Copyright (C) 2022-2022 Fuwn <contact@fuwn.me>
+// SPDX-License-Identifier: GPL-3.0-only
+
+#[cfg(test)]
+mod test {
+  use germ::{
+    ast::{Ast, Node, Parser},
+    EXAMPLE_GEMTEXT,
+  };
+
+  fn parse(source: &str) -> Vec<Node> {
+    Parser::new(source.as_bytes()).collect::<std::io::Result<_>>().unwrap()
+  }
+
+  #[test]
+  fn parser_matches_ast() {
+    for source in [
+      EXAMPLE_GEMTEXT,
+      "* item1\n* 2\nhi text\n",
+      "=> /test  hi there\r\n```alt\r\nbody\r\n\r\n```\r\n",
+      "a\n```\nunterminated\n",
+      "a\n```\r\nunterminated",
+      "=>\n=\n",
+      "",
+      "\n",
+    ] {
+      assert_eq!(&parse(source), Ast::from_string(source).inner());
+    }
+  }
+
+  #[test]
+  fn parser_yields_nodes_as_lines_are_read() {
+    let mut parser = Parser::new("# Hi\n> quote\n".as_bytes());
+
+    assert_eq!(parser.next().unwrap().unwrap(), Node::Heading {
+      level: 1,
+      text:  "Hi".to_string(),
+    });
+    assert_eq!(
+      parser.next().unwrap().unwrap(),
+      Node::Blockquote("quote".to_string())
+    );
+    assert_eq!(parser.next().unwrap().unwrap(), Node::Whitespace);
+    assert!(parser.next().is_none());
+  }
+
+  #[test]
+  fn parser_reports_invalid_utf8() {
+    let mut parser = Parser::new(&[0xff, b'\n'][..]);
+
+    assert!(parser.next().unwrap().is_err());
+    assert!(parser.next().is_none());
+  }
+
+  #[tokio::test]
+  async fn parser_reads_asynchronously() {
+    let mut parser = Parser::new(EXAMPLE_GEMTEXT.as_bytes());
+    let mut nodes = vec![];
+
+    while let Some(node) = parser.next_async().await {
+      nodes.push(node.unwrap());
+    }
+
+    assert_eq!(&nodes, Ast::from_string(EXAMPLE_GEMTEXT).inner());
+  }
+
+  #[test]
+  fn parser_collects_diagnostics() {
+    let mut parser = Parser::new("=>\n```".as_bytes());
+
+    while parser.next().is_some() {}
+
+    assert_eq!(parser.diagnostics().len(), 2);
+  }
+}
@@ -0,0 +1,100 @@
+// This file is part of Germ <https://github.com/gemrest/germ>.
+// Copyright (C) 2022-2022 Fuwn <contact@fuwn.me>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+//
+// Copyright (C) 2022-2022 Fuwn <contact@fuwn.me>
+// SPDX-License-Identifier: GPL-3.0-only
+
+#[cfg(test)]
+mod test {
+  use {
+    germ::{
+      ast::{borrowed, Ast},
+      convert::{render_with, Renderer},
+    },
+    std::borrow::Cow,
+  };
+
+  #[derive(Default)]
+  struct Hooks(Vec<String>);
+
+  impl Renderer for Hooks {
+    fn start_document(&mut self) { self.0.push("start".to_string()); }
+
+    fn render_text(&mut self, text: &str) {
+      self.0.push(format!("text {text}"));
+    }
+
+    fn render_link(&mut self, to: &str, text: Option<&str>) {
+      self.0.push(format!("link {to} {text:?}"));
+    }
+
+    fn render_heading(&mut self, level: usize, text: &str) {
+      self.0.push(format!("heading {level} {text}"));
+    }
+
+    fn render_list(&mut self, items: &[Cow<'_, str>]) {
+      self.0.push(format!("list {}", items.join(",")));
+    }
+
+    fn render_blockquote(&mut self, text: &str) {
+      self.0.push(format!("blockquote {text}"));
+    }
+
+    fn render_preformatted_text(&mut self, alt_text: Option<&str>, text: &str) {
+      self.0.push(format!("preformatted {alt_text:?} {text:?}"));
+    }
+
+    fn render_whitespace(&mut self) { self.0.push("whitespace".to_string()); }
+
+    fn end_document(&mut self) { self.0.push("end".to_string()); }
+  }
+
+  #[test]
+  fn render_with_calls_a_hook_per_node() {
+    let mut hooks = Hooks::default();
+
+    render_with(
+      &Ast::from_string("# Hi\ntext\n=> /a\n* 1\n* 2\n> q\n```alt\npre\n```\n"),
+      &mut hooks,
+    );
+
+    assert_eq!(hooks.0, [
+      "start",
+      "heading 1 Hi",
+      "text text",
+      "link /a None",
+      "list 1,2",
+      "blockquote q",
+      "preformatted Some(\"alt\") \"pre\\n\"",
+      "whitespace",
+      "end",
+    ]);
+  }
+
+  #[test]
+  fn render_with_borrowed_ast_and_default_hooks() {
+    struct Links(usize);
+
+    impl Renderer for Links {
+      fn render_link(&mut self, _to: &str, _text: Option<&str>) { self.0 += 1; }
+    }
+
+    let mut links = Links(0);
+
+    render_with(borrowed::Ast::from_value("=> /a\ntext\n=> /b B"), &mut links);
+
+    assert_eq!(links.0, 2);
+  }
+}
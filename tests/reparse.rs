@@ -0,0 +1,93 @@
+// This file is part of Germ <https://github.com/gemrest/germ>.
+// Copyright (C) 2022-2022 Fuwn <contact@fuwn.me>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+//
+// Copyright (C) 2022-2022 Fuwn <contact@fuwn.me>
+// SPDX-License-Identifier: GPL-3.0-only
+
+#[cfg(test)]
+mod test {
+  use germ::{ast::Ast, EXAMPLE_GEMTEXT};
+
+  /// Apply an edit to `source`, and check that re-parsing gives the same nodes
+  /// and spans as parsing the edited source in full
+  fn check(source: &str, edit: std::ops::Range<usize>, replacement: &str) {
+    let mut edited = source.to_string();
+
+    edited.replace_range(edit.clone(), replacement);
+
+    let (ast, changed) =
+      Ast::from_string(source).reparse(edit, replacement, &edited);
+    let expected = Ast::from_string(edited.as_str());
+
+    assert_eq!(ast, expected, "{edited:?}");
+    assert_eq!(ast.spans(), expected.spans(), "{edited:?}");
+    assert!(changed.end <= ast.len());
+  }
+
+  #[test]
+  fn reparse_matches_a_full_parse() {
+    for (source, edit, replacement) in [
+      ("# Hi\n\ntext\n=> / Home\n", 6..6, "more "),
+      ("# Hi\n\ntext\n=> / Home\n", 6..11, ""),
+      ("a\n* 1\nb\n* 2\nc\n", 6..8, "* 3"),
+      ("a\n* 1\nb\n* 2\nc\n", 2..5, "text"),
+      ("a\n```\nb\n```\nc\n", 2..5, "```rs"),
+      ("a\n```\nb\n```\nc\n", 8..11, "``"),
+      ("a\n```\nb\n```\nc\n", 0..0, "```\n"),
+      ("a\r\nb\r\n\r\nc", 3..3, "```"),
+      ("a\nb", 3..3, "\n"),
+      ("a\nb\n", 4..4, "c"),
+      ("a\nb\n", 2..4, ""),
+      ("", 0..0, "# Hi\n* a"),
+      ("é\nb\nc", 0..2, "* ü"),
+    ] {
+      check(source, edit, replacement);
+    }
+  }
+
+  #[test]
+  fn reparse_matches_a_full_parse_for_every_single_line_edit() {
+    let lines = EXAMPLE_GEMTEXT
+      .match_indices('\n')
+      .map(|(index, _)| index)
+      .collect::<Vec<_>>();
+
+    for window in lines.windows(2) {
+      let line = window[0] + 1..window[1];
+
+      for replacement in ["", "```", "* item", "=> /", "text", "\n\n"] {
+        check(EXAMPLE_GEMTEXT, line.clone(), replacement);
+      }
+    }
+  }
+
+  #[test]
+  fn reparse_reports_only_changed_nodes() {
+    let source = "# A\n\nb\n\n# C\n\nd\n";
+    let (ast, changed) =
+      Ast::from_string(source).reparse(6..7, "e", "# A\n\ne\n\n# C\n\nd\n");
+
+    assert_eq!(changed, 2..3);
+    assert_eq!(ast.spans()[6].span().start().line(), 7);
+  }
+
+  #[test]
+  fn reparse_parses_ast_without_spans_in_full() {
+    let (ast, changed) = Ast::from_nodes(vec![]).reparse(0..0, "a\nb", "a\nb");
+
+    assert_eq!(ast, Ast::from_string("a\nb"));
+    assert_eq!(changed, 0..2);
+  }
+}
@@ -0,0 +1,73 @@
+// This file is part of Germ <https://github.com/gemrest/germ>.
+// Copyright (C) 2022-2022 Fuwn <contact@fuwn.me>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+//
+// Copyright (C) 2022-2022 Fuwn <contact@fuwn.me>
+// SPDX-License-Identifier: GPL-3.0-only
+
+#[cfg(test)]
+mod test {
+  use germ::ast::{Ast, Node};
+
+  #[test]
+  fn sections_nest_by_heading_level() {
+    let ast = Ast::from_string(
+      "Intro\n# A\nA text\n### A.a\n## A.b\nA.b text\n# B\n## B.a",
+    );
+    let sections = ast.sections();
+
+    assert_eq!(sections.len(), 3);
+    assert_eq!(sections[0].level(), 0);
+    assert_eq!(sections[0].body(), [Node::Text("Intro".to_string())]);
+
+    let a = &sections[1];
+
+    assert_eq!(a.body(), [Node::Text("A text".to_string())]);
+    assert_eq!(
+      a.children()
+        .iter()
+        .map(|section| (section.level(), section.heading().unwrap()))
+        .collect::<Vec<_>>(),
+      vec![(3, "A.a"), (2, "A.b")]
+    );
+    assert!(a.children()[0].body().is_empty());
+    assert_eq!(sections[2].children()[0].heading(), Some("B.a"));
+  }
+
+  #[test]
+  fn documents_without_headings_are_one_section() {
+    let ast = Ast::from_string("Just\ntext");
+
+    assert_eq!(ast.sections().len(), 1);
+    assert!(ast.outline().sections().is_empty());
+    assert!(Ast::from_string("").sections().is_empty());
+  }
+
+  #[test]
+  fn outline_as_gemtext_and_html() {
+    let ast =
+      Ast::from_string("Intro\n## Getting Started!\n### Step 1\n## FAQ");
+
+    assert_eq!(
+      ast.outline().to_gemtext(),
+      "=> #getting-started Getting Started!\n=> #step-1 Step 1\n=> #faq FAQ"
+    );
+    assert_eq!(
+      ast.outline().to_html(),
+      "<ul><li><a href=\"#getting-started\">Getting Started!</a><ul><li><a \
+       href=\"#step-1\">Step 1</a></li></ul></li><li><a \
+       href=\"#faq\">FAQ</a></li></ul>"
+    );
+  }
+}
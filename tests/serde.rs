@@ -0,0 +1,100 @@
+// This file is part of Germ <https://github.com/gemrest/germ>.
+// Copyright (C) 2022-2022 Fuwn <contact@fuwn.me>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+//
+// Copyright (C) 2022-2022 Fuwn <contact@fuwn.me>
+// SPDX-License-Identifier: GPL-3.0-only
+
+#[cfg(test)]
+mod test {
+  use germ::{
+    ast::{Ast, Node},
+    meta::Meta,
+    request::Status,
+  };
+
+  #[test]
+  fn serde_status_as_numeric_code() {
+    assert_eq!(serde_json::to_string(&Status::NotFound).unwrap(), "51");
+    assert_eq!(
+      serde_json::from_str::<Status>("31").unwrap(),
+      Status::PermanentRedirect
+    );
+  }
+
+  #[test]
+  fn serde_meta_round_trip() {
+    let meta = Meta::from_string("text/gemini; lang=en");
+    let json = serde_json::to_string(&meta).unwrap();
+
+    assert_eq!(json, r#"{"mime":"text/gemini","parameters":{"lang":"en"}}"#);
+    assert_eq!(serde_json::from_str::<Meta>(&json).unwrap(), meta);
+  }
+
+  #[test]
+  fn serde_meta_parameters_are_sorted() {
+    let meta =
+      Meta::from_string("text/gemini; lang=en; charset=utf-8; b=2; a=1; z=26");
+    let json = serde_json::to_string(&meta).unwrap();
+
+    assert_eq!(
+      json,
+      r#"{"mime":"text/gemini","parameters":{"a":"1","b":"2","charset":"utf-8","lang":"en","z":"26"}}"#
+    );
+    assert_eq!(serde_json::from_str::<Meta>(&json).unwrap(), meta);
+  }
+
+  #[test]
+  fn serde_node_shapes() {
+    for (node, json) in [
+      (Node::Text("Hi".to_string()), r#"{"type":"text","value":"Hi"}"#),
+      (
+        Node::Link { to: "/".to_string(), text: None },
+        r#"{"type":"link","value":{"to":"/","text":null}}"#,
+      ),
+      (
+        Node::Heading { level: 2, text: "Hi".to_string() },
+        r#"{"type":"heading","value":{"level":2,"text":"Hi"}}"#,
+      ),
+      (
+        Node::List(vec!["1".to_string(), "2".to_string()]),
+        r#"{"type":"list","value":["1","2"]}"#,
+      ),
+      (
+        Node::Blockquote("Q".to_string()),
+        r#"{"type":"blockquote","value":"Q"}"#,
+      ),
+      (
+        Node::PreformattedText {
+          alt_text: Some("rs".to_string()),
+          text:     "fn main() {}\n".to_string(),
+        },
+        r#"{"type":"preformatted_text","value":{"alt_text":"rs","text":"fn main() {}\n"}}"#,
+      ),
+      (Node::Whitespace, r#"{"type":"whitespace"}"#),
+    ] {
+      assert_eq!(serde_json::to_string(&node).unwrap(), json);
+      assert_eq!(serde_json::from_str::<Node>(json).unwrap(), node);
+    }
+  }
+
+  #[test]
+  fn serde_ast_round_trip() {
+    let ast = Ast::from_string("# Hi\n=> / Home\n");
+    let json = serde_json::to_string(&ast).unwrap();
+
+    assert!(json.starts_with(r#"[{"type":"heading""#));
+    assert_eq!(serde_json::from_str::<Ast>(&json).unwrap(), ast);
+  }
+}
@@ -0,0 +1,103 @@
+// This file is part of Germ <https://github.com/gemrest/germ>.
+// Copyright (C) 2022-2022 Fuwn <contact@fuwn.me>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+//
+// Copyright (C) 2022-2022 Fuwn <contact@fuwn.me>
+// SPDX-License-Identifier: GPL-3.0-only
+
+#[cfg(test)]
+mod test {
+  use germ::{
+    ast::{slug, Ast, Slugger},
+    convert::{from_ast, HtmlOptions, Target},
+  };
+
+  #[test]
+  fn slug_keeps_letters_and_numbers_of_any_script() {
+    assert_eq!(slug("  Hello, World!  "), "hello-world");
+    assert_eq!(slug("Привет мир"), "привет-мир");
+    assert_eq!(slug("日本語 ３"), "日本語-３");
+    assert_eq!(slug("C++ & Rust: 2024"), "c--rust-2024");
+    assert_eq!(slug("ÀÉÎ"), "àéî");
+    assert_eq!(slug("!!!"), "");
+  }
+
+  #[test]
+  fn slugger_numbers_repeated_slugs() {
+    let mut slugger = Slugger::new();
+
+    assert_eq!(
+      ["Intro", "Intro", "intro-1", "Intro", "!", "?", "Section"]
+        .map(|text| slugger.slug(text)),
+      [
+        "intro",
+        "intro-1",
+        "intro-1-1",
+        "intro-2",
+        "section",
+        "section-1",
+        "section-2"
+      ]
+    );
+  }
+
+  #[test]
+  fn markdown_anchors_match_html_ids() {
+    let ast = Ast::from_string("#### FAQ\n# FAQ\n## !!!");
+
+    assert_eq!(
+      from_ast(&ast, &Target::HTML),
+      "<p id=\"faq\">FAQ</p><h1 id=\"faq-1\">FAQ</h1><h2 \
+       id=\"section\">!!!</h2>"
+    );
+    assert_eq!(
+      from_ast(&ast, &Target::Markdown),
+      " <a id=\"faq\"></a>FAQ\n# <a id=\"faq-1\"></a>FAQ\n## <a \
+       id=\"section\"></a>!!!\n"
+    );
+  }
+
+  #[test]
+  fn headings_without_letters_or_numbers_get_ids() {
+    let ast = Ast::from_string("# !!!\n## ???");
+
+    assert_eq!(
+      from_ast(&ast, &Target::HTML),
+      "<h1 id=\"section\">!!!</h1><h2 id=\"section-1\">???</h2>"
+    );
+    assert_eq!(
+      ast.outline().to_gemtext(),
+      "=> #section !!!\n=> #section-1 ???"
+    );
+  }
+
+  #[test]
+  fn html_heading_ids_match_the_outline() {
+    let ast = Ast::from_string("# FAQ\n## Why?\n# FAQ\n## Why?");
+
+    assert_eq!(
+      from_ast(&ast, &Target::HTML),
+      "<h1 id=\"faq\">FAQ</h1><h2 id=\"why\">Why?</h2><h1 \
+       id=\"faq-1\">FAQ</h1><h2 id=\"why-1\">Why?</h2>"
+    );
+    assert_eq!(
+      ast.outline().to_gemtext(),
+      "=> #faq FAQ\n=> #why Why?\n=> #faq-1 FAQ\n=> #why-1 Why?"
+    );
+    assert_eq!(
+      HtmlOptions::new().heading_ids(false).to_html(&ast),
+      "<h1>FAQ</h1><h2>Why?</h2><h1>FAQ</h1><h2>Why?</h2>"
+    );
+  }
+}
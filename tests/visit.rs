@@ -0,0 +1,91 @@
+// This file is part of Germ <https://github.com/gemrest/germ>.
+// Copyright (C) 2022-2022 Fuwn <contact@fuwn.me>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+//
+// Copyright (C) 2022-2022 Fuwn <contact@fuwn.me>
+// SPDX-License-Identifier: GPL-3.0-only
+
+#[cfg(test)]
+mod test {
+  use germ::{
+    ast::{borrowed, Ast, Fold, Node, Visit},
+    EXAMPLE_GEMTEXT,
+  };
+
+  #[derive(Default)]
+  struct Counter {
+    headings: Vec<usize>,
+    nodes:    usize,
+  }
+
+  impl Visit for Counter {
+    fn visit_node(&mut self, node: &borrowed::Node<'_>) {
+      self.nodes += 1;
+
+      if let borrowed::Node::Heading { level, .. } = node {
+        self.headings.push(*level);
+      }
+    }
+  }
+
+  #[test]
+  fn visit_owned_and_borrowed_asts_alike() {
+    let mut owned = Counter::default();
+    let mut borrowed = Counter::default();
+
+    Ast::from_string(EXAMPLE_GEMTEXT).visit(&mut owned);
+    borrowed::Ast::from_value(EXAMPLE_GEMTEXT).visit(&mut borrowed);
+
+    assert_eq!(owned.headings, vec![1, 2, 3]);
+    assert_eq!(owned.headings, borrowed.headings);
+    assert_eq!(owned.nodes, borrowed.nodes);
+  }
+
+  struct Rewrite;
+
+  impl Fold for Rewrite {
+    fn fold_link(&mut self, to: String, text: Option<String>) -> Option<Node> {
+      Some(Node::Link { to: to.replace("gemini://", "https://"), text })
+    }
+
+    fn fold_whitespace(&mut self) -> Option<Node> { None }
+  }
+
+  #[test]
+  fn fold_rewrites_and_drops_nodes() {
+    let ast = Ast::from_string("=> gemini://gem.rest/ GemRest\n\n> Quote")
+      .fold(&mut Rewrite);
+
+    assert_eq!(ast.inner(), &vec![
+      Node::Link {
+        to:   "https://gem.rest/".to_string(),
+        text: Some("GemRest".to_string()),
+      },
+      Node::Blockquote("Quote".to_string()),
+    ]);
+    assert_eq!(ast.spans()[1].span().start().line(), 3);
+  }
+
+  #[test]
+  fn fold_defaults_keep_the_ast_unchanged() {
+    struct Identity;
+
+    impl Fold for Identity {}
+
+    assert_eq!(
+      Ast::from_string(EXAMPLE_GEMTEXT).fold(&mut Identity),
+      Ast::from_string(EXAMPLE_GEMTEXT)
+    );
+  }
+}
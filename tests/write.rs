@@ -0,0 +1,148 @@
+// This file is part of Germ <https://github.com/gemrest/germ>.
+// Copyright (C) 2022-2022 Fuwn <contact@fuwn.me>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+//
+// Copyright (C) 2022-2022 Fuwn <contact@fuwn.me>
+// SPDX-License-Identifier: GPL-3.0-only
+
+#[cfg(test)]
+mod test {
+  use {
+    germ::{
+      ast::{Ast, Parser},
+      convert::{
+        from_ast, write_ast, write_ast_fmt, write_nodes, LinkStyle, Target,
+      },
+      EXAMPLE_GEMTEXT,
+    },
+    std::{fmt, io},
+  };
+
+  const TARGETS: [Target; 5] = [
+    Target::HTML,
+    Target::TrustedHTML,
+    Target::Markdown,
+    Target::PlainText(LinkStyle::Inline),
+    Target::PlainText(LinkStyle::References),
+  ];
+
+  /// Accepts a limited number of bytes, then fails
+  struct Limited(usize);
+
+  impl io::Write for Limited {
+    fn write(&mut self, buffer: &[u8]) -> io::Result<usize> {
+      if buffer.len() > self.0 {
+        return Err(io::Error::new(io::ErrorKind::WriteZero, "full"));
+      }
+
+      self.0 -= buffer.len();
+
+      Ok(buffer.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> { Ok(()) }
+  }
+
+  #[test]
+  fn write_ast_matches_from_ast() {
+    let ast = Ast::from_string(EXAMPLE_GEMTEXT);
+
+    for target in TARGETS {
+      let mut output = vec![];
+
+      write_ast(&ast, &target, &mut output).unwrap();
+
+      assert_eq!(String::from_utf8(output).unwrap(), from_ast(&ast, &target));
+    }
+  }
+
+  #[test]
+  fn write_ast_fmt_writes_into_formatters() {
+    struct Html<'a>(&'a Ast);
+
+    impl fmt::Display for Html<'_> {
+      fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_ast_fmt(self.0, &Target::HTML, f)
+      }
+    }
+
+    let ast = Ast::from_string("# Hi\n=> /a A");
+
+    assert_eq!(Html(&ast).to_string(), from_ast(&ast, &Target::HTML));
+  }
+
+  #[test]
+  fn write_nodes_streams_parsed_gemtext() {
+    for target in TARGETS {
+      let mut output = vec![];
+
+      write_nodes(
+        Parser::new(EXAMPLE_GEMTEXT.as_bytes()),
+        &target,
+        &mut output,
+      )
+      .unwrap();
+
+      assert_eq!(
+        String::from_utf8(output).unwrap(),
+        from_ast(&Ast::from_string(EXAMPLE_GEMTEXT), &target)
+      );
+    }
+  }
+
+  /// Counts the writes made to it
+  #[derive(Default)]
+  struct Counted(usize, Vec<u8>);
+
+  impl io::Write for Counted {
+    fn write(&mut self, buffer: &[u8]) -> io::Result<usize> {
+      self.0 += 1;
+
+      self.1.write(buffer)
+    }
+
+    fn flush(&mut self) -> io::Result<()> { Ok(()) }
+  }
+
+  #[test]
+  fn write_output_is_buffered() {
+    let ast = Ast::from_string(EXAMPLE_GEMTEXT);
+    let mut counted = Counted::default();
+
+    write_ast(&ast, &Target::HTML, &mut counted).unwrap();
+
+    assert_eq!(counted.0, 1);
+    assert_eq!(counted.1, from_ast(&ast, &Target::HTML).into_bytes());
+  }
+
+  #[test]
+  fn write_errors_are_reported() {
+    let error = write_ast(
+      &Ast::from_string(EXAMPLE_GEMTEXT),
+      &Target::HTML,
+      &mut Limited(10),
+    )
+    .unwrap_err();
+
+    assert_eq!(error.kind(), io::ErrorKind::WriteZero);
+    assert!(
+      write_nodes(
+        Parser::new(&b"text\n\xff\n"[..]),
+        &Target::Markdown,
+        &mut vec![]
+      )
+      .is_err()
+    );
+  }
+}